@@ -0,0 +1,210 @@
+//! Options that make the game easier to see, read and control.
+//!
+//! Colors and text sizes are applied through the [`Theme`], so the arena and the
+//! menus follow them alike; object sizes, game speed and input handling are applied
+//! to the match itself.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::settings::PlayerType;
+use crate::core::storage;
+use crate::pong::Ball;
+use crate::theme::{Theme, ThemeColor};
+
+const STORAGE_NAME: &str = "accessibility";
+
+/// Theme used in place of the chosen one while high contrast is on.
+pub const HIGH_CONTRAST_THEME: &str = "high contrast";
+/// How much bigger the ball and paddles are with large objects on.
+const LARGE_OBJECT_SCALE: f32 = 1.5;
+
+pub const TEXT_SCALES: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+pub const GAME_SPEEDS: std::ops::RangeInclusive<f32> = 0.5..=1.0;
+
+/// `value` clamped into `range`, or `fallback` if it isn't a number.
+fn within(value: f32, range: std::ops::RangeInclusive<f32>, fallback: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(*range.start(), *range.end())
+    } else {
+        fallback
+    }
+}
+
+/// Colors for the two sides that stay distinct with common color vision deficiencies.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SidePalette {
+    /// The theme's own paddle colors.
+    #[default]
+    Theme,
+    /// Okabe-Ito blue and orange, distinct with red-green deficiencies.
+    BlueOrange,
+    /// Okabe-Ito sky blue and vermillion, distinct with blue-yellow deficiencies too.
+    SkyVermillion,
+}
+
+impl SidePalette {
+    pub const ALL: [SidePalette; 3] = [
+        SidePalette::Theme,
+        SidePalette::BlueOrange,
+        SidePalette::SkyVermillion,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SidePalette::Theme => "Theme Colors",
+            SidePalette::BlueOrange => "Blue / Orange",
+            SidePalette::SkyVermillion => "Sky / Vermillion",
+        }
+    }
+
+    fn colors(&self) -> Option<[Color; 2]> {
+        match self {
+            SidePalette::Theme => None,
+            SidePalette::BlueOrange => Some([
+                Color::srgb_u8(0x00, 0x72, 0xB2),
+                Color::srgb_u8(0xE6, 0x9F, 0x00),
+            ]),
+            SidePalette::SkyVermillion => Some([
+                Color::srgb_u8(0x56, 0xB4, 0xE9),
+                Color::srgb_u8(0xD5, 0x5E, 0x00),
+            ]),
+        }
+    }
+}
+
+/// How the keyboard moves a paddle.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum InputMode {
+    /// The paddle moves while a direction is held.
+    #[default]
+    Hold,
+    /// A press starts the paddle moving that way; pressing it again stops it.
+    Toggle,
+}
+
+/// Saved to `accessibility.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub palette: SidePalette,
+    pub high_contrast: bool,
+    /// Multiplies every text size, in [`TEXT_SCALES`].
+    pub text_scale: f32,
+    pub large_objects: bool,
+    /// Relative speed of the whole game, in [`GAME_SPEEDS`].
+    pub game_speed: f32,
+    pub input: InputMode,
+    /// Whether effects may flash large parts of the screen.
+    pub flashing: bool,
+}
+
+impl AccessibilitySettings {
+    /// Loads the saved settings, bringing a text scale or game speed edited out of
+    /// range back into it.
+    pub fn load() -> Self {
+        let mut settings: Self = storage::load(STORAGE_NAME);
+        let defaults = Self::default();
+        settings.text_scale = within(settings.text_scale, TEXT_SCALES, defaults.text_scale);
+        settings.game_speed = within(settings.game_speed, GAME_SPEEDS, defaults.game_speed);
+        settings
+    }
+
+    /// Name of the theme to use when `chosen` is picked in the settings.
+    pub fn theme_name<'a>(&self, chosen: &'a str) -> &'a str {
+        if self.high_contrast {
+            HIGH_CONTRAST_THEME
+        } else {
+            chosen
+        }
+    }
+
+    /// Applies the palette and text scale to `theme`.
+    pub fn adapt(&self, theme: &mut Theme) {
+        if let Some([left, right]) = self.palette.colors() {
+            theme.left_paddle = ThemeColor(left);
+            theme.right_paddle = ThemeColor(right);
+        }
+
+        let fonts = &mut theme.fonts;
+        for size in [
+            &mut fonts.score_size,
+            &mut fonts.clock_size,
+            &mut fonts.heading_size,
+            &mut fonts.text_size,
+        ] {
+            *size *= self.text_scale;
+        }
+    }
+
+    pub fn object_scale(&self) -> f32 {
+        if self.large_objects {
+            LARGE_OBJECT_SCALE
+        } else {
+            1.
+        }
+    }
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            palette: SidePalette::Theme,
+            high_contrast: false,
+            text_scale: 1.,
+            large_objects: false,
+            game_speed: 1.,
+            input: InputMode::Hold,
+            flashing: true,
+        }
+    }
+}
+
+/// Grows the ball as it is spawned; its collider scales with it.
+fn scale_ball(
+    trigger: Trigger<OnAdd, Ball>,
+    settings: Res<AccessibilitySettings>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if let Ok(mut transform) = transforms.get_mut(trigger.entity()) {
+        transform.scale = Vec3::splat(settings.object_scale());
+    }
+}
+
+/// Makes paddles taller as they are spawned, keeping their width.
+fn scale_paddle(
+    trigger: Trigger<OnAdd, PlayerType>,
+    settings: Res<AccessibilitySettings>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if let Ok(mut transform) = transforms.get_mut(trigger.entity()) {
+        transform.scale.y = settings.object_scale();
+    }
+}
+
+fn apply_game_speed(settings: Res<AccessibilitySettings>, mut time: ResMut<Time<Virtual>>) {
+    if settings.is_changed() {
+        time.set_relative_speed(settings.game_speed);
+    }
+}
+
+impl storage::Persistent for AccessibilitySettings {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AccessibilitySettings::load())
+            .add_observer(scale_ball)
+            .add_observer(scale_paddle)
+            .add_systems(
+                Update,
+                (
+                    apply_game_speed,
+                    storage::save_on_change::<AccessibilitySettings>,
+                ),
+            );
+    }
+}
@@ -0,0 +1,306 @@
+//! Goals players unlock over their matches.
+//!
+//! Achievements are defined in `assets/achievements.json`, with the built-in list
+//! used when the file is missing. Each one names the moment it is checked at and
+//! conditions on the stats of the running match, so a new achievement only needs a
+//! new entry. Stats are kept for the first human player in regular matches.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::settings::{GameMode, GameSettings, PlayerType};
+use crate::core::states::GameState;
+use crate::core::storage;
+use crate::pong::constants::ball::MAX_BALL_SPEED;
+use crate::pong::{AfterPointScored, OnPaddleHit, RestartMatch, Score, ScoreField};
+
+const STORAGE_NAME: &str = "achievements";
+const DEFINITIONS_FILE: &str = "assets/achievements.json";
+const BUNDLED: &str = include_str!("../assets/achievements.json");
+
+/// Moment in a match an achievement is checked at.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Moment {
+    PaddleHit,
+    PointScored,
+    MatchWon,
+}
+
+/// Bounds on one stat of the match; a stat the match doesn't have fails the condition.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Condition {
+    pub stat: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_least: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_most: Option<f32>,
+}
+
+impl Condition {
+    fn holds(&self, stats: &MatchStats) -> bool {
+        stats.get(&self.stat).is_some_and(|value| {
+            self.at_least.is_none_or(|least| value >= least)
+                && self.at_most.is_none_or(|most| value <= most)
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub on: Moment,
+    pub conditions: Vec<Condition>,
+}
+
+impl Achievement {
+    pub fn is_met(&self, moment: Moment, stats: &MatchStats) -> bool {
+        self.on == moment
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(stats))
+    }
+}
+
+/// Every achievement there is to unlock.
+#[derive(Resource, Clone, Debug)]
+pub struct AchievementDefinitions(pub Vec<Achievement>);
+
+impl AchievementDefinitions {
+    /// Definitions from `path`, or the built-in ones when it can't be read.
+    pub fn load_from(path: &Path) -> Self {
+        let bundled = || serde_json::from_str(BUNDLED).expect("built-in achievements are valid");
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self(bundled());
+        };
+        match serde_json::from_str(&contents) {
+            Ok(achievements) => Self(achievements),
+            Err(error) => {
+                warn!("Ignoring achievements in {}: {}", path.display(), error);
+                Self(bundled())
+            }
+        }
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(Path::new(DEFINITIONS_FILE))
+    }
+}
+
+/// Unlocked achievements by id, with when they were unlocked in seconds since the
+/// Unix epoch. Saved to `achievements.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct AchievementProgress {
+    pub unlocked: BTreeMap<String, u64>,
+}
+
+impl AchievementProgress {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains_key(id)
+    }
+}
+
+/// An achievement was unlocked just now.
+#[derive(Event, Clone, Debug)]
+pub struct AchievementUnlocked(pub Achievement);
+
+/// What has happened so far in the running match, from the tracked player's side.
+#[derive(Resource, Clone, PartialEq, Debug, Default)]
+pub struct MatchStats {
+    /// Side of the tracked player, or `None` when nobody is tracked.
+    pub side: Option<ScoreField>,
+    /// Easy (0) to Impossible (2) level of a computer opponent.
+    pub opponent_level: Option<f32>,
+    /// Paddle hits by either side since the last point.
+    pub rally_hits: u32,
+    pub longest_rally: u32,
+    pub points: u32,
+    pub conceded: u32,
+    /// Most points the player has been behind by.
+    pub max_deficit: u32,
+    /// Speed of the player's latest return as a share of the top speed.
+    pub return_speed: Option<f32>,
+}
+
+impl MatchStats {
+    pub fn new(settings: &GameSettings) -> Self {
+        let players = [*settings.get_player1(), *settings.get_player2()];
+        let side = match players {
+            _ if settings.get_mode() != GameMode::Match => None,
+            [PlayerType::Human, _] => Some(ScoreField::Left),
+            [_, PlayerType::Human] => Some(ScoreField::Right),
+            _ => None,
+        };
+        let opponent = match side {
+            Some(ScoreField::Left) => Some(players[1]),
+            Some(ScoreField::Right) => Some(players[0]),
+            None => None,
+        };
+
+        Self {
+            side,
+            opponent_level: match opponent {
+                Some(PlayerType::Computer(difficulty)) => difficulty.level(),
+                _ => None,
+            },
+            ..default()
+        }
+    }
+
+    /// Value of the stat named `name` in achievement definitions.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        match name {
+            "opponent_level" => self.opponent_level,
+            "rally_hits" => Some(self.rally_hits as f32),
+            "longest_rally" => Some(self.longest_rally as f32),
+            "points" => Some(self.points as f32),
+            "conceded" => Some(self.conceded as f32),
+            "max_deficit" => Some(self.max_deficit as f32),
+            "return_speed" => self.return_speed,
+            _ => None,
+        }
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Unlocks every achievement met at `moment`, announcing each one.
+fn check_achievements(
+    commands: &mut Commands,
+    moment: Moment,
+    stats: &MatchStats,
+    definitions: &AchievementDefinitions,
+    progress: &mut ResMut<AchievementProgress>,
+) {
+    if stats.side.is_none() {
+        return;
+    }
+    for achievement in &definitions.0 {
+        if !progress.is_unlocked(&achievement.id) && achievement.is_met(moment, stats) {
+            progress
+                .unlocked
+                .insert(achievement.id.clone(), unix_time());
+            commands.trigger(AchievementUnlocked(achievement.clone()));
+        }
+    }
+}
+
+fn start_tracking(mut commands: Commands, settings: Res<GameSettings>) {
+    commands.insert_resource(MatchStats::new(&settings));
+}
+
+fn restart_tracking(_: Trigger<RestartMatch>, mut commands: Commands, settings: Res<GameSettings>) {
+    commands.insert_resource(MatchStats::new(&settings));
+}
+
+fn track_paddle_hit(
+    trigger: Trigger<OnPaddleHit>,
+    mut commands: Commands,
+    mut stats: ResMut<MatchStats>,
+    definitions: Res<AchievementDefinitions>,
+    mut progress: ResMut<AchievementProgress>,
+) {
+    stats.rally_hits += 1;
+    stats.longest_rally = stats.longest_rally.max(stats.rally_hits);
+    stats.return_speed =
+        (stats.side == Some(trigger.side)).then_some(trigger.speed / MAX_BALL_SPEED);
+
+    check_achievements(
+        &mut commands,
+        Moment::PaddleHit,
+        &stats,
+        &definitions,
+        &mut progress,
+    );
+}
+
+fn track_point(
+    _: Trigger<AfterPointScored>,
+    mut commands: Commands,
+    score: Res<Score>,
+    mut stats: ResMut<MatchStats>,
+    definitions: Res<AchievementDefinitions>,
+    mut progress: ResMut<AchievementProgress>,
+) {
+    let (points, conceded) = match stats.side {
+        Some(ScoreField::Left) => (score.player1(), score.player2()),
+        Some(ScoreField::Right) => (score.player2(), score.player1()),
+        None => return,
+    };
+    stats.points = points;
+    stats.conceded = conceded;
+    stats.max_deficit = stats.max_deficit.max(conceded.saturating_sub(points));
+    stats.rally_hits = 0;
+    stats.return_speed = None;
+
+    check_achievements(
+        &mut commands,
+        Moment::PointScored,
+        &stats,
+        &definitions,
+        &mut progress,
+    );
+}
+
+fn track_match_end(
+    mut commands: Commands,
+    score: Res<Score>,
+    stats: Res<MatchStats>,
+    definitions: Res<AchievementDefinitions>,
+    mut progress: ResMut<AchievementProgress>,
+) {
+    // Nothing is earned from an opponent walking away.
+    if score.forfeited().is_some() {
+        return;
+    }
+    let (points, conceded) = match stats.side {
+        Some(ScoreField::Left) => (score.player1(), score.player2()),
+        Some(ScoreField::Right) => (score.player2(), score.player1()),
+        None => return,
+    };
+    if points > conceded {
+        check_achievements(
+            &mut commands,
+            Moment::MatchWon,
+            &stats,
+            &definitions,
+            &mut progress,
+        );
+    }
+}
+
+impl storage::Persistent for AchievementProgress {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AchievementDefinitions::load())
+            .insert_resource(AchievementProgress::load())
+            .init_resource::<MatchStats>()
+            .add_observer(restart_tracking)
+            .add_observer(track_paddle_hit)
+            .add_observer(track_point)
+            .add_systems(OnEnter(GameState::Playing), start_tracking)
+            .add_systems(OnEnter(GameState::Endgame), track_match_end)
+            .add_systems(Update, storage::save_on_change::<AchievementProgress>);
+    }
+}
@@ -0,0 +1,54 @@
+mod cues;
+mod library;
+mod observers;
+mod settings;
+mod synth;
+mod systems;
+
+use bevy::audio::AddAudioSource;
+use bevy::prelude::*;
+
+use crate::core::states::GameState;
+use crate::core::storage;
+
+use cues::*;
+use observers::*;
+use synth::SynthSound;
+use systems::*;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<SynthSound>()
+            .add_audio_source::<BallTone>()
+            .insert_resource(AudioSettings::load())
+            .add_observer(play_sfx)
+            .add_observer(paddle_hit)
+            .add_observer(wall_bounce)
+            .add_observer(point_scored)
+            .add_observer(menu_focused)
+            .add_systems(OnEnter(GameState::Endgame), match_end)
+            .add_systems(
+                Startup,
+                (
+                    (library::load_library, start_music).chain(),
+                    start_ball_tone,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_music_volume,
+                    storage::save_on_change::<AudioSettings>,
+                    steer_ball_tone,
+                    announce_score,
+                ),
+            );
+    }
+}
+
+pub use cues::MenuFocused;
+pub use library::Sfx;
+pub use observers::PlaySfx;
+pub use settings::AudioSettings;
@@ -0,0 +1,251 @@
+//! Sounds that let the game be followed without seeing it.
+//!
+//! While a match runs, the ball hums a steady tone: panned by where it is across the
+//! arena, pitched by how far above or below the player's paddle it is, and louder the
+//! closer it gets to the player's goal. Earcons announce who scored and the new score,
+//! and mark each menu item as it gains focus.
+
+use bevy::audio::{Decodable, Source, Volume};
+use bevy::prelude::*;
+use std::f32::consts::{FRAC_PI_4, TAU};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::library::Sfx;
+use super::observers::PlaySfx;
+use super::settings::AudioSettings;
+use super::synth::{Note, SynthSound, Waveform};
+use crate::core::settings::{GameSettings, PlayerType};
+use crate::core::states::{GameState, PausedState};
+use crate::pong::{Ball, Score, ScoreField};
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Pitch of the ball level with the player's paddle; it rises and falls by up to
+/// an octave across the arena.
+const BASE_PITCH: f32 = 440.;
+/// Share of each sample the tone moves towards its targets, so changes glide
+/// instead of clicking.
+const GLIDE: f32 = 0.002;
+const TONE_GAIN: f32 = 0.25;
+/// Loudness of the tone with the ball at the far end of the arena.
+const FAR_GAIN: f32 = 0.35;
+
+/// A menu item gained keyboard focus or the pointer.
+#[derive(Event)]
+pub struct MenuFocused;
+
+/// Targets the ball tone glides towards, shared with the audio thread.
+#[derive(Default)]
+struct ToneTargets {
+    frequency: AtomicU32,
+    pan: AtomicU32,
+    gain: AtomicU32,
+}
+
+impl ToneTargets {
+    fn set(&self, frequency: f32, pan: f32, gain: f32) {
+        self.frequency.store(frequency.to_bits(), Ordering::Relaxed);
+        self.pan.store(pan.to_bits(), Ordering::Relaxed);
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> (f32, f32, f32) {
+        (
+            f32::from_bits(self.frequency.load(Ordering::Relaxed)),
+            f32::from_bits(self.pan.load(Ordering::Relaxed)),
+            f32::from_bits(self.gain.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Endless stereo sine whose pitch, pan and loudness follow [`ToneTargets`].
+#[derive(Asset, TypePath, Clone)]
+pub struct BallTone {
+    targets: Arc<ToneTargets>,
+}
+
+pub struct BallToneDecoder {
+    targets: Arc<ToneTargets>,
+    frequency: f32,
+    pan: f32,
+    gain: f32,
+    phase: f32,
+    /// The right channel's sample, waiting its turn after the left one.
+    right: Option<f32>,
+}
+
+impl Iterator for BallToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let (frequency, pan, gain) = self.targets.get();
+        self.frequency += (frequency - self.frequency) * GLIDE;
+        self.pan += (pan - self.pan) * GLIDE;
+        self.gain += (gain - self.gain) * GLIDE;
+
+        let value = (self.phase * TAU).sin() * self.gain;
+        self.phase = (self.phase + self.frequency / SAMPLE_RATE as f32).fract();
+
+        // Equal-power panning keeps the tone as loud in the middle as at the sides.
+        let angle = (self.pan + 1.) * FRAC_PI_4;
+        self.right = Some(value * angle.sin());
+        Some(value * angle.cos())
+    }
+}
+
+impl Source for BallToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Decodable for BallTone {
+    type DecoderItem = f32;
+    type Decoder = BallToneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let (frequency, pan, _) = self.targets.get();
+        BallToneDecoder {
+            targets: self.targets.clone(),
+            frequency,
+            pan,
+            gain: 0.,
+            phase: 0.,
+            right: None,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct BallToneTargets(Arc<ToneTargets>);
+
+pub fn start_ball_tone(mut commands: Commands, mut tones: ResMut<Assets<BallTone>>) {
+    let targets = Arc::new(ToneTargets::default());
+    targets.set(BASE_PITCH, 0., 0.);
+
+    commands.spawn((
+        AudioPlayer(tones.add(BallTone {
+            targets: targets.clone(),
+        })),
+        PlaybackSettings::ONCE,
+    ));
+    commands.insert_resource(BallToneTargets(targets));
+}
+
+/// The side whose player the cues are for: the first human paddle, or the left one.
+fn listener_side(settings: &GameSettings) -> ScoreField {
+    if *settings.get_player1() != PlayerType::Human && *settings.get_player2() == PlayerType::Human
+    {
+        ScoreField::Right
+    } else {
+        ScoreField::Left
+    }
+}
+
+pub fn steer_ball_tone(
+    targets: Res<BallToneTargets>,
+    audio: Res<AudioSettings>,
+    settings: Res<GameSettings>,
+    state: Res<State<GameState>>,
+    paused: Res<State<PausedState>>,
+    balls: Query<&Transform, With<Ball>>,
+    paddles: Query<(&Transform, &ScoreField), With<PlayerType>>,
+) {
+    let playing = *state.get() == GameState::Playing && *paused.get() == PausedState::Playing;
+    let half_size = settings.get_arena().size() / 2.;
+    let side = listener_side(&settings);
+    let goal_x = match side {
+        ScoreField::Left => -half_size.x,
+        ScoreField::Right => half_size.x,
+    };
+    let paddle_y = paddles
+        .iter()
+        .find(|(_, field)| **field == side)
+        .map_or(0., |(transform, _)| transform.translation.y);
+    // Follow the ball closest to the player's goal.
+    let ball = balls.iter().min_by(|a, b| {
+        (a.translation.x - goal_x)
+            .abs()
+            .total_cmp(&(b.translation.x - goal_x).abs())
+    });
+
+    let (Some(ball), true, true) = (ball, playing, audio.cues) else {
+        let (frequency, pan, _) = targets.0.get();
+        targets.0.set(frequency, pan, 0.);
+        return;
+    };
+
+    let height = ((ball.translation.y - paddle_y) / half_size.y).clamp(-1., 1.);
+    let pan = (ball.translation.x / half_size.x).clamp(-1., 1.);
+    let distance = ((ball.translation.x - goal_x).abs() / (2. * half_size.x)).clamp(0., 1.);
+    let gain = TONE_GAIN * audio.sfx_volume() * (1. - (1. - FAR_GAIN) * distance);
+    targets.0.set(BASE_PITCH * height.exp2(), pan, gain);
+}
+
+/// Announces each point: a rising motif when the player scored and a falling one
+/// when the opponent did, then the player's points as high pips and the
+/// opponent's as low ones.
+pub fn announce_score(
+    mut commands: Commands,
+    mut last: Local<(u32, u32)>,
+    audio: Res<AudioSettings>,
+    settings: Res<GameSettings>,
+    score: Res<Score>,
+    mut synths: ResMut<Assets<SynthSound>>,
+) {
+    if !score.is_changed() {
+        return;
+    }
+    let current = (score.player1(), score.player2());
+    let previous = std::mem::replace(&mut *last, current);
+    // A new match resets the score rather than adding to it.
+    if !audio.cues || current.0 + current.1 <= previous.0 + previous.1 {
+        return;
+    }
+
+    let ((own, other), scored) = match listener_side(&settings) {
+        ScoreField::Left => (current, current.0 > previous.0),
+        ScoreField::Right => ((current.1, current.0), current.1 > previous.1),
+    };
+
+    let mut notes = if scored {
+        vec![Note::new(523.25, 0.12), Note::new(783.99, 0.2)]
+    } else {
+        vec![Note::new(392., 0.12), Note::new(261.63, 0.2)]
+    };
+    notes.push(Note::rest(0.2));
+    for (points, pitch) in [(own, 880.), (other, 330.)] {
+        for _ in 0..points {
+            notes.extend([Note::new(pitch, 0.07), Note::rest(0.08)]);
+        }
+        notes.push(Note::rest(0.2));
+    }
+
+    commands.spawn((
+        AudioPlayer(synths.add(SynthSound::new(Waveform::Sine, notes))),
+        PlaybackSettings::DESPAWN.with_volume(Volume::new(audio.sfx_volume())),
+    ));
+}
+
+pub fn menu_focused(_: Trigger<MenuFocused>, mut commands: Commands, audio: Res<AudioSettings>) {
+    if audio.cues {
+        commands.trigger(PlaySfx::new(Sfx::MenuFocus));
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use super::synth::{Note, SynthSound, Waveform};
+
+const AUDIO_DIR: &str = "audio";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Sfx {
+    PaddleHit,
+    WallBounce,
+    Score,
+    MatchEnd,
+    MenuSelect,
+    MenuFocus,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 6] = [
+        Sfx::PaddleHit,
+        Sfx::WallBounce,
+        Sfx::Score,
+        Sfx::MatchEnd,
+        Sfx::MenuSelect,
+        Sfx::MenuFocus,
+    ];
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            Sfx::PaddleHit => "paddle_hit.ogg",
+            Sfx::WallBounce => "wall_bounce.ogg",
+            Sfx::Score => "score.ogg",
+            Sfx::MatchEnd => "match_end.ogg",
+            Sfx::MenuSelect => "menu_select.ogg",
+            Sfx::MenuFocus => "menu_focus.ogg",
+        }
+    }
+
+    fn synth(&self) -> SynthSound {
+        match self {
+            Sfx::PaddleHit => SynthSound::new(Waveform::Square, [Note::new(440., 0.08)]),
+            Sfx::WallBounce => SynthSound::new(Waveform::Square, [Note::new(220., 0.06)]),
+            Sfx::Score => SynthSound::new(
+                Waveform::Triangle,
+                [Note::new(523.25, 0.1), Note::new(392., 0.25)],
+            ),
+            Sfx::MatchEnd => SynthSound::new(
+                Waveform::Triangle,
+                [
+                    Note::new(392., 0.12),
+                    Note::new(523.25, 0.12),
+                    Note::new(659.25, 0.12),
+                    Note::new(783.99, 0.4),
+                ],
+            ),
+            Sfx::MenuSelect => SynthSound::new(Waveform::Sine, [Note::new(880., 0.05)]),
+            Sfx::MenuFocus => SynthSound::new(Waveform::Sine, [Note::new(660., 0.04)]),
+        }
+    }
+}
+
+const MUSIC_FILE: &str = "music.ogg";
+
+fn music_synth() -> SynthSound {
+    let bassline = [110., 130.81, 164.81, 130.81, 98., 123.47, 146.83, 123.47];
+    let notes = bassline
+        .iter()
+        .flat_map(|frequency| [Note::new(*frequency, 0.35), Note::rest(0.15)])
+        .collect::<Vec<_>>();
+
+    SynthSound::new(Waveform::Triangle, notes)
+}
+
+#[derive(Clone)]
+pub enum SoundHandle {
+    File(Handle<AudioSource>),
+    Synth(Handle<SynthSound>),
+}
+
+impl SoundHandle {
+    fn load(
+        file_name: &str,
+        synth: impl FnOnce() -> SynthSound,
+        asset_server: &AssetServer,
+        synths: &mut Assets<SynthSound>,
+    ) -> Self {
+        let path = format!("{}/{}", AUDIO_DIR, file_name);
+        let on_disk = FileAssetReader::get_base_path()
+            .join("assets")
+            .join(&path)
+            .exists();
+
+        if on_disk {
+            SoundHandle::File(asset_server.load(path))
+        } else {
+            SoundHandle::Synth(synths.add(synth()))
+        }
+    }
+
+    pub fn spawn(&self, commands: &mut Commands, settings: PlaybackSettings) -> Entity {
+        match self {
+            SoundHandle::File(handle) => commands.spawn((AudioPlayer(handle.clone()), settings)),
+            SoundHandle::Synth(handle) => commands.spawn((AudioPlayer(handle.clone()), settings)),
+        }
+        .id()
+    }
+}
+
+/// Sounds loaded from `assets/audio`, or synthesized when the file is missing.
+#[derive(Resource)]
+pub struct SoundLibrary {
+    sfx: HashMap<Sfx, SoundHandle>,
+    music: SoundHandle,
+}
+
+impl SoundLibrary {
+    pub fn sfx(&self, sfx: Sfx) -> &SoundHandle {
+        &self.sfx[&sfx]
+    }
+
+    pub fn music(&self) -> &SoundHandle {
+        &self.music
+    }
+}
+
+pub fn load_library(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut synths: ResMut<Assets<SynthSound>>,
+) {
+    let sfx = Sfx::ALL
+        .iter()
+        .map(|sfx| {
+            let handle =
+                SoundHandle::load(sfx.file_name(), || sfx.synth(), &asset_server, &mut synths);
+            (*sfx, handle)
+        })
+        .collect();
+    let music = SoundHandle::load(MUSIC_FILE, music_synth, &asset_server, &mut synths);
+
+    commands.insert_resource(SoundLibrary { sfx, music });
+}
@@ -0,0 +1,62 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use super::library::{Sfx, SoundLibrary};
+use super::settings::AudioSettings;
+use crate::pong::constants::ball::MAX_BALL_SPEED;
+use crate::pong::{OnPaddleHit, OnPointScored, OnWallBounce};
+
+const MAX_PITCH_SHIFT: f32 = 0.75;
+
+#[derive(Event)]
+pub struct PlaySfx {
+    sfx: Sfx,
+    pitch: f32,
+}
+
+impl PlaySfx {
+    pub fn new(sfx: Sfx) -> Self {
+        Self { sfx, pitch: 1. }
+    }
+
+    /// Raises the pitch the faster the ball is travelling.
+    pub fn with_ball_speed(mut self, speed: f32) -> Self {
+        self.pitch = 1. + (speed / MAX_BALL_SPEED).clamp(0., 1.) * MAX_PITCH_SHIFT;
+        self
+    }
+}
+
+pub fn play_sfx(
+    trigger: Trigger<PlaySfx>,
+    mut commands: Commands,
+    library: Option<Res<SoundLibrary>>,
+    settings: Res<AudioSettings>,
+) {
+    let Some(library) = library else {
+        return;
+    };
+
+    let event = trigger.event();
+    library.sfx(event.sfx).spawn(
+        &mut commands,
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(settings.sfx_volume()))
+            .with_speed(event.pitch),
+    );
+}
+
+pub fn paddle_hit(trigger: Trigger<OnPaddleHit>, mut commands: Commands) {
+    commands.trigger(PlaySfx::new(Sfx::PaddleHit).with_ball_speed(trigger.speed));
+}
+
+pub fn wall_bounce(trigger: Trigger<OnWallBounce>, mut commands: Commands) {
+    commands.trigger(PlaySfx::new(Sfx::WallBounce).with_ball_speed(trigger.speed));
+}
+
+pub fn point_scored(_: Trigger<OnPointScored>, mut commands: Commands) {
+    commands.trigger(PlaySfx::new(Sfx::Score));
+}
+
+pub fn match_end(mut commands: Commands) {
+    commands.trigger(PlaySfx::new(Sfx::MatchEnd));
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::storage;
+
+const STORAGE_NAME: &str = "audio";
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+    /// Whether the ball tone and score and menu earcons play.
+    pub cues: bool,
+}
+
+impl AudioSettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            sfx: 1.0,
+            music: 0.5,
+            cues: false,
+        }
+    }
+}
+
+impl storage::Persistent for AudioSettings {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
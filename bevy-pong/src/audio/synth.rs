@@ -0,0 +1,139 @@
+use bevy::audio::{Decodable, Source};
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 44_100;
+const ATTACK: f32 = 0.005;
+
+#[derive(Clone, Copy)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+}
+
+impl Waveform {
+    fn sample(&self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    0.6
+                } else {
+                    -0.6
+                }
+            }
+            Waveform::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+        }
+    }
+}
+
+/// A single tone; a frequency of zero is a rest.
+#[derive(Clone, Copy)]
+pub struct Note {
+    frequency: f32,
+    duration: f32,
+}
+
+impl Note {
+    pub fn new(frequency: f32, duration: f32) -> Self {
+        Self {
+            frequency,
+            duration,
+        }
+    }
+
+    pub fn rest(duration: f32) -> Self {
+        Self::new(0., duration)
+    }
+}
+
+/// Procedurally generated sound used when no audio file is available.
+#[derive(Asset, TypePath, Clone)]
+pub struct SynthSound {
+    waveform: Waveform,
+    notes: Arc<[Note]>,
+}
+
+impl SynthSound {
+    pub fn new(waveform: Waveform, notes: impl Into<Arc<[Note]>>) -> Self {
+        Self {
+            waveform,
+            notes: notes.into(),
+        }
+    }
+}
+
+pub struct SynthDecoder {
+    waveform: Waveform,
+    notes: Arc<[Note]>,
+    note: usize,
+    sample: u32,
+    phase: f32,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let note = *self.notes.get(self.note)?;
+        let length = (note.duration * SAMPLE_RATE as f32) as u32;
+
+        if self.sample >= length {
+            self.note += 1;
+            self.sample = 0;
+            self.phase = 0.;
+            return self.next();
+        }
+
+        let time = self.sample as f32 / SAMPLE_RATE as f32;
+        self.sample += 1;
+
+        if note.frequency <= 0. {
+            return Some(0.);
+        }
+
+        let attack = (time / ATTACK).min(1.);
+        let release = 1. - time / note.duration;
+        let value = self.waveform.sample(self.phase) * attack * release * release;
+
+        self.phase = (self.phase + note.frequency / SAMPLE_RATE as f32).fract();
+        Some(value * 0.5)
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let seconds = self.notes.iter().map(|note| note.duration).sum();
+        Some(Duration::from_secs_f32(seconds))
+    }
+}
+
+impl Decodable for SynthSound {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            waveform: self.waveform,
+            notes: self.notes.clone(),
+            note: 0,
+            sample: 0,
+            phase: 0.,
+        }
+    }
+}
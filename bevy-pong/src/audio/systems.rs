@@ -0,0 +1,28 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use super::library::SoundLibrary;
+use super::settings::AudioSettings;
+
+#[derive(Component)]
+pub struct Music;
+
+pub fn start_music(
+    mut commands: Commands,
+    library: Res<SoundLibrary>,
+    settings: Res<AudioSettings>,
+) {
+    let music = library.music().spawn(
+        &mut commands,
+        PlaybackSettings::LOOP.with_volume(Volume::new(settings.music_volume())),
+    );
+    commands.entity(music).insert(Music);
+}
+
+pub fn update_music_volume(settings: Res<AudioSettings>, music: Query<&AudioSink, With<Music>>) {
+    if settings.is_changed() {
+        for sink in music.iter() {
+            sink.set_volume(settings.music_volume());
+        }
+    }
+}
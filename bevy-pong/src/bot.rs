@@ -0,0 +1,398 @@
+//! Paddles steered by an external program.
+//!
+//! Every fixed tick the game writes one JSON line per bot paddle, such as
+//!
+//! ```text
+//! {"tick":12,"side":"left","ball_x":-41.5,"ball_y":3.2,"ball_vx":-221.4,"ball_vy":39.7,
+//!  "paddle1_y":-50.0,"paddle2_y":-42.0,"score1":0,"score2":1,"half_width":600.0,"half_height":500.0}
+//! ```
+//!
+//! and expects a reply line: `up`, `down`, `stay`, or a number from -1 (full speed
+//! down) to 1 (full speed up). The reply is picked up on the next tick, which waits a
+//! few milliseconds at most for it. Without a reply by then the paddle holds still
+//! for that tick, and the late reply is skipped.
+//!
+//! A bot endpoint is either `tcp:HOST:PORT`, which the game connects to, or a command
+//! line that the game launches, talking over the program's stdin and stdout. Both are
+//! started off the main thread, and retried every few seconds while they fail.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::settings::{GameSettings, PlayerType};
+use crate::core::states::{GameState, PausedState};
+use crate::core::storage;
+use crate::env::{observe, Observation};
+use crate::pong::{ExternalInput, MatchClock, ScoreField};
+
+const STORAGE_NAME: &str = "bots";
+/// Half a fixed tick, so waiting on bots never holds up the game.
+const MAX_TIMEOUT_MS: u64 = 7;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const RETRY_INTERVAL: Duration = Duration::from_secs(3);
+/// Observations a bot may fall behind on reading before it counts as stalled.
+const OBSERVATION_BACKLOG: usize = 64;
+
+/// Where each player's bot runs, and how long a tick waits for a reply that hasn't
+/// arrived yet, capped at half a tick. Read from `bots.json` in the data directory and
+/// overridden from the command line.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct BotSettings {
+    pub player1: String,
+    pub player2: String,
+    pub timeout_ms: u64,
+}
+
+impl BotSettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    pub fn endpoint(&self, side: ScoreField) -> &str {
+        match side {
+            ScoreField::Left => &self.player1,
+            ScoreField::Right => &self.player2,
+        }
+    }
+
+    pub fn set_endpoint(&mut self, side: ScoreField, endpoint: String) {
+        match side {
+            ScoreField::Left => self.player1 = endpoint,
+            ScoreField::Right => self.player2 = endpoint,
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.min(MAX_TIMEOUT_MS))
+    }
+}
+
+impl Default for BotSettings {
+    fn default() -> Self {
+        Self {
+            player1: "tcp:127.0.0.1:7878".into(),
+            player2: "tcp:127.0.0.1:7879".into(),
+            timeout_ms: 4,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BotMessage {
+    tick: u64,
+    side: &'static str,
+    #[serde(flatten)]
+    observation: Observation,
+}
+
+/// Parses a reply line into a paddle direction.
+pub fn parse_reply(line: &str) -> Option<f32> {
+    match line.trim() {
+        "up" => Some(1.),
+        "down" => Some(-1.),
+        "stay" => Some(0.),
+        number => number
+            .parse::<f32>()
+            .ok()
+            .filter(|direction| direction.is_finite())
+            .map(|direction| direction.clamp(-1., 1.)),
+    }
+}
+
+#[derive(Debug)]
+pub enum BotError {
+    Connect(String, io::Error),
+    Disconnected,
+    Stalled,
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::Connect(endpoint, error) => {
+                write!(f, "could not start bot '{}': {}", endpoint, error)
+            }
+            BotError::Disconnected => write!(f, "bot disconnected"),
+            BotError::Stalled => write!(f, "bot stopped reading observations"),
+        }
+    }
+}
+
+/// A running bot: observations are written by one thread and reply lines read by
+/// another, so a bot that stops reading or writing never holds up the game.
+pub struct BotConnection {
+    observations: SyncSender<String>,
+    replies: Mutex<Receiver<String>>,
+    /// Observations sent whose replies have not been read yet.
+    outstanding: u32,
+    child: Option<Child>,
+}
+
+impl BotConnection {
+    pub fn connect(endpoint: &str) -> Result<Self, BotError> {
+        let error = |error| BotError::Connect(endpoint.into(), error);
+
+        if let Some(address) = endpoint.strip_prefix("tcp:") {
+            let stream = connect_tcp(address).map_err(error)?;
+            stream.set_nodelay(true).map_err(error)?;
+            let reader = stream.try_clone().map_err(error)?;
+            return Ok(Self::new(Box::new(stream), reader, None));
+        }
+
+        let mut parts = endpoint.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| error(io::Error::new(io::ErrorKind::InvalidInput, "empty command")))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(error)?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        Ok(Self::new(Box::new(stdin), stdout, Some(child)))
+    }
+
+    fn new(
+        mut writer: Box<dyn Write + Send + Sync>,
+        reader: impl io::Read + Send + 'static,
+        child: Option<Child>,
+    ) -> Self {
+        let (observations, lines) = mpsc::sync_channel::<String>(OBSERVATION_BACKLOG);
+        thread::spawn(move || {
+            for line in lines {
+                if writeln!(writer, "{}", line)
+                    .and_then(|_| writer.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            observations,
+            replies: Mutex::new(replies),
+            outstanding: 0,
+            child,
+        }
+    }
+
+    /// Queues one observation for the bot; its reply is taken by a later
+    /// [`poll`](Self::poll).
+    pub fn send(&mut self, message: &impl Serialize) -> Result<(), BotError> {
+        let line = serde_json::to_string(message).map_err(|_| BotError::Disconnected)?;
+        self.observations
+            .try_send(line)
+            .map_err(|error| match error {
+                TrySendError::Full(_) => BotError::Stalled,
+                TrySendError::Disconnected(_) => BotError::Disconnected,
+            })?;
+        self.outstanding += 1;
+        Ok(())
+    }
+
+    /// Takes the reply to the latest observation, waiting until `deadline` at most.
+    /// Replies to older observations are skipped. `Ok(None)` means the bot was too
+    /// slow or sent something unreadable.
+    pub fn poll(&mut self, deadline: Instant) -> Result<Option<f32>, BotError> {
+        let replies = self.replies.get_mut().map_err(|_| BotError::Disconnected)?;
+        while self.outstanding > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match replies.recv_timeout(remaining) {
+                Ok(reply) => {
+                    self.outstanding -= 1;
+                    if self.outstanding == 0 {
+                        return Ok(parse_reply(&reply));
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => return Err(BotError::Disconnected),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to");
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+impl Drop for BotConnection {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Connection to one player's bot.
+#[derive(Default)]
+enum BotSlot {
+    #[default]
+    Idle,
+    Connecting(Mutex<Receiver<Result<BotConnection, BotError>>>),
+    Connected(BotConnection),
+    /// The last attempt failed, or the bot went away, at this time.
+    Failed(Instant),
+}
+
+impl BotSlot {
+    /// Connects to `endpoint` on another thread.
+    fn connect(endpoint: String) -> Self {
+        let (sender, attempt) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(BotConnection::connect(&endpoint));
+        });
+        BotSlot::Connecting(Mutex::new(attempt))
+    }
+}
+
+/// Connections to the bots playing the current match.
+#[derive(Resource, Default)]
+pub struct BotConnections {
+    left: BotSlot,
+    right: BotSlot,
+}
+
+impl BotConnections {
+    fn get_mut(&mut self, side: ScoreField) -> &mut BotSlot {
+        match side {
+            ScoreField::Left => &mut self.left,
+            ScoreField::Right => &mut self.right,
+        }
+    }
+}
+
+const SIDES: [ScoreField; 2] = [ScoreField::Left, ScoreField::Right];
+
+fn is_bot(settings: &GameSettings, side: ScoreField) -> bool {
+    let player = match side {
+        ScoreField::Left => settings.get_player1(),
+        ScoreField::Right => settings.get_player2(),
+    };
+    *player == PlayerType::External
+}
+
+/// Starts connecting to the configured bot of every bot player that isn't connected,
+/// and picks up the connections that are ready.
+fn connect_bots(
+    settings: Res<GameSettings>,
+    bots: Res<BotSettings>,
+    mut connections: ResMut<BotConnections>,
+) {
+    for side in SIDES {
+        let slot = connections.get_mut(side);
+        if !is_bot(&settings, side) {
+            *slot = BotSlot::Idle;
+            continue;
+        }
+
+        let next = match slot {
+            BotSlot::Connected(_) => None,
+            BotSlot::Failed(at) if at.elapsed() < RETRY_INTERVAL => None,
+            BotSlot::Idle | BotSlot::Failed(_) => {
+                Some(BotSlot::connect(bots.endpoint(side).into()))
+            }
+            BotSlot::Connecting(attempt) => {
+                match attempt.get_mut().map(|attempt| attempt.try_recv()) {
+                    Ok(Err(TryRecvError::Empty)) => None,
+                    Ok(Ok(Ok(bot))) => Some(BotSlot::Connected(bot)),
+                    Ok(Ok(Err(error))) => {
+                        warn!("{}; the paddle will hold still until it connects", error);
+                        Some(BotSlot::Failed(Instant::now()))
+                    }
+                    _ => Some(BotSlot::Failed(Instant::now())),
+                }
+            }
+        };
+        if let Some(next) = next {
+            *slot = next;
+        }
+    }
+}
+
+/// Takes each bot's reply to the previous tick's observation and sends it this one.
+fn exchange_with_bots(world: &mut World) {
+    let observation = observe(world);
+    let tick = world.resource::<MatchClock>().ticks();
+    // All bots share one wait, so a tick is held up by one timeout at most.
+    let deadline = Instant::now() + world.resource::<BotSettings>().timeout();
+
+    world.resource_scope(|world, mut connections: Mut<BotConnections>| {
+        for side in SIDES {
+            let slot = connections.get_mut(side);
+            let BotSlot::Connected(bot) = slot else {
+                continue;
+            };
+
+            let message = BotMessage {
+                tick,
+                side: match side {
+                    ScoreField::Left => "left",
+                    ScoreField::Right => "right",
+                },
+                observation,
+            };
+            let direction = match bot
+                .poll(deadline)
+                .and_then(|direction| bot.send(&message).map(|_| direction))
+            {
+                Ok(direction) => direction.unwrap_or(0.),
+                Err(error) => {
+                    warn!("{}; the paddle will hold still until it reconnects", error);
+                    *slot = BotSlot::Failed(Instant::now());
+                    0.
+                }
+            };
+
+            world.resource_mut::<ExternalInput>().set(side, direction);
+        }
+    });
+}
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BotSettings::load())
+            .init_resource::<BotConnections>()
+            .add_systems(Update, connect_bots.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                FixedPreUpdate,
+                exchange_with_bots
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(PausedState::Playing)),
+            );
+    }
+}
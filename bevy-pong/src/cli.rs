@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::bot::BotSettings;
+use crate::core::settings::{GameSettings, PaddleMovement, PlayerType};
+use crate::core::states::GameState;
+use crate::pong::ScoreField;
+use crate::spectator::DEFAULT_DELAY_MS;
+use crate::tuner::TunerConfig;
+
+pub const USAGE: &str = "\
+Usage: bevy_pong [OPTIONS]
+
+Starts at the main menu unless a match option is given.
+
+Match options:
+  --p1 <PLAYER>      Left paddle: human, bot, lan, ai, ai:easy, ai:difficult,
+                     ai:impossible, or ai:<LEVEL> for a skill level from 0 (easy)
+                     upwards; lan leaves the side open to a player joining the
+                     game streamed with --stream
+  --p2 <PLAYER>      Right paddle (same values as --p1)
+  --points <N>       Points needed to win the match
+  --arena <ARENA>    classic, wide or compact
+  --seed <N>         Seed for all match randomness
+  --time <SECONDS>   Timed match: highest score when time runs out wins
+  --momentum         Paddles accelerate, brake and can dash
+
+Bot options:
+  --bot1 <ENDPOINT>  Left paddle is a bot at ENDPOINT: tcp:HOST:PORT, or a command
+                     to launch that reads observations on stdin and replies on stdout
+  --bot2 <ENDPOINT>  Right paddle is a bot at ENDPOINT
+  --bot-timeout <MS> Milliseconds a tick waits for a late bot reply (default 4, at most 7)
+
+Batch options:
+  --headless         Run without a window; every player must be an AI
+  --matches <N>      Number of matches to play in headless mode (default 1)
+  --report <FILE>    Write headless match results to FILE as JSON
+
+Analytics options:
+  --events <FILE>    Write gameplay events to FILE as JSON lines: serves, hits,
+                     bounces, points, pauses and match results, each with the
+                     time and match clock tick it happened at
+
+Spectator options:
+  --stream <PORT>    Stream the game to spectators connecting on PORT and
+                     announce it to Join LAN Game screens on the network
+  --name <NAME>      Name the streamed game is announced under (default: user name)
+  --spectate <HOST:PORT>
+                     Watch a game streamed from HOST:PORT instead of playing
+  --spectate-delay <MS>
+                     Milliseconds the view trails the host (default 250)
+  --play <SIDE>      With --spectate, play the left or right side of the watched
+                     game if the host left it to a lan player; steer with player
+                     1's keys
+
+Leaderboard options:
+  --leaderboard-server <PORT>
+                     Run a local leaderboard server on PORT instead of playing,
+                     keeping results in leaderboard_server.json in the data
+                     directory; games submit to the server in leaderboard.json
+
+Tuning options:
+  --tune             Calibrate the AI difficulty presets with headless self-play
+                     and save them to ai_presets.json in the data directory;
+                     match options set the arena, points and movement used
+  --generations <N>  Rounds of evolutionary search per preset (default 10)
+  --population <N>   Candidate profiles per round (default 8)
+  --tune-matches <N> Matches played to measure each candidate (default 20)
+
+  -h, --help         Print this message";
+
+pub enum CliError {
+    Help,
+    Invalid(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Help => write!(f, "{}", USAGE),
+            CliError::Invalid(message) => write!(f, "error: {}\n\n{}", message, USAGE),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CliOptions {
+    /// Match to start immediately, skipping the menus.
+    pub settings: Option<GameSettings>,
+    pub headless: bool,
+    pub matches: u32,
+    pub report: Option<PathBuf>,
+    /// File to write gameplay events to.
+    pub events: Option<PathBuf>,
+    /// Bot endpoints given on the command line, on top of the saved ones.
+    pub bots: Option<BotSettings>,
+    /// Calibrate the AI presets instead of playing.
+    pub tune: Option<TunerConfig>,
+    /// Port to stream the game to spectators on.
+    pub stream: Option<u16>,
+    /// Port to run a leaderboard server on instead of playing.
+    pub leaderboard_server: Option<u16>,
+    /// Name to announce the streamed game under.
+    pub name: String,
+    /// Host to watch instead of playing.
+    pub spectate: Option<String>,
+    pub spectate_delay: Duration,
+    /// Side of the watched game to join as a player.
+    pub play: Option<ScoreField>,
+}
+
+#[derive(Default, PartialEq)]
+struct TuningFlags {
+    generations: Option<u32>,
+    population: Option<usize>,
+    matches: Option<u32>,
+}
+
+fn value<T: FromStr>(flag: &str, arg: Option<String>) -> Result<T, CliError>
+where
+    T::Err: fmt::Display,
+{
+    let arg = arg.ok_or_else(|| CliError::Invalid(format!("{} requires a value", flag)))?;
+    arg.parse()
+        .map_err(|error| CliError::Invalid(format!("{}: {}", flag, error)))
+}
+
+fn positive(flag: &str, arg: Option<String>) -> Result<u32, CliError> {
+    match value(flag, arg)? {
+        0 => Err(CliError::Invalid(format!("{} must be at least 1", flag))),
+        n => Ok(n),
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<CliOptions, CliError> {
+    let mut options = CliOptions::default();
+    let mut matches = None;
+    let mut spectate_delay = None;
+    let mut tuning = TuningFlags::default();
+    let mut args = args.into_iter();
+
+    while let Some(flag) = args.next() {
+        let settings = &mut options.settings;
+        match flag.as_str() {
+            "--p1" => settings
+                .get_or_insert_with(default)
+                .update_players(1, value(&flag, args.next())?),
+            "--p2" => settings
+                .get_or_insert_with(default)
+                .update_players(2, value(&flag, args.next())?),
+            "--points" => settings
+                .get_or_insert_with(default)
+                .set_points_to_win(positive(&flag, args.next())?),
+            "--arena" => settings
+                .get_or_insert_with(default)
+                .set_arena(value(&flag, args.next())?),
+            "--seed" => settings
+                .get_or_insert_with(default)
+                .set_seed(Some(value(&flag, args.next())?)),
+            "--time" => settings
+                .get_or_insert_with(default)
+                .set_time_limit(Some(positive(&flag, args.next())?)),
+            "--momentum" => {
+                let settings = settings.get_or_insert_with(default);
+                settings.set_movement(PaddleMovement {
+                    momentum: true,
+                    ..settings.get_movement()
+                })
+            }
+            "--bot1" | "--bot2" => {
+                let (player_num, side) = if flag == "--bot1" {
+                    (1, ScoreField::Left)
+                } else {
+                    (2, ScoreField::Right)
+                };
+                options
+                    .bots
+                    .get_or_insert_with(BotSettings::load)
+                    .set_endpoint(side, value(&flag, args.next())?);
+                settings
+                    .get_or_insert_with(default)
+                    .update_players(player_num, PlayerType::External);
+            }
+            "--bot-timeout" => {
+                options
+                    .bots
+                    .get_or_insert_with(BotSettings::load)
+                    .timeout_ms = positive(&flag, args.next())? as u64
+            }
+            "--tune" => {
+                options.tune.get_or_insert_with(default);
+            }
+            "--generations" => {
+                tuning.generations = Some(positive(&flag, args.next())?);
+            }
+            "--population" => {
+                tuning.population = Some(positive(&flag, args.next())? as usize);
+            }
+            "--tune-matches" => {
+                tuning.matches = Some(positive(&flag, args.next())?);
+            }
+            "--stream" => options.stream = Some(value(&flag, args.next())?),
+            "--name" => options.name = value(&flag, args.next())?,
+            "--spectate" => options.spectate = Some(value(&flag, args.next())?),
+            "--spectate-delay" => spectate_delay = Some(value(&flag, args.next())?),
+            "--play" => {
+                options.play = match value::<String>(&flag, args.next())?.as_str() {
+                    "left" => Some(ScoreField::Left),
+                    "right" => Some(ScoreField::Right),
+                    side => {
+                        return Err(CliError::Invalid(format!(
+                            "--play: invalid side '{}', expected left or right",
+                            side
+                        )))
+                    }
+                }
+            }
+            "--leaderboard-server" => options.leaderboard_server = Some(value(&flag, args.next())?),
+            "--headless" => options.headless = true,
+            "--matches" => matches = Some(positive(&flag, args.next())?),
+            "--report" => options.report = Some(value(&flag, args.next())?),
+            "--events" => options.events = Some(value(&flag, args.next())?),
+            "-h" | "--help" => return Err(CliError::Help),
+            _ => return Err(CliError::Invalid(format!("unknown option '{}'", flag))),
+        }
+    }
+
+    if !options.headless && (matches.is_some() || options.report.is_some()) {
+        return Err(CliError::Invalid(
+            "--matches and --report require --headless".into(),
+        ));
+    }
+
+    if options.spectate.is_some()
+        && (options.settings.is_some()
+            || options.headless
+            || options.tune.is_some()
+            || options.events.is_some())
+    {
+        return Err(CliError::Invalid(
+            "--spectate can't be combined with match, headless, tuning or event options".into(),
+        ));
+    }
+    if options.spectate.is_none() && spectate_delay.is_some() {
+        return Err(CliError::Invalid(
+            "--spectate-delay requires --spectate".into(),
+        ));
+    }
+    if options.spectate.is_none() && options.play.is_some() {
+        return Err(CliError::Invalid("--play requires --spectate".into()));
+    }
+    if options.stream.is_none() && !options.name.is_empty() {
+        return Err(CliError::Invalid("--name requires --stream".into()));
+    }
+    if options.name.is_empty() {
+        options.name = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "Pong".into());
+    }
+    // Players see frames as they arrive, to keep up with the ball.
+    let delay = if options.play.is_some() {
+        0
+    } else {
+        DEFAULT_DELAY_MS
+    };
+    options.spectate_delay = Duration::from_millis(spectate_delay.unwrap_or(delay));
+
+    match options.tune.as_mut() {
+        Some(config) => {
+            config.generations = tuning.generations.unwrap_or(config.generations);
+            config.population = tuning.population.unwrap_or(config.population);
+            config.matches = tuning.matches.unwrap_or(config.matches);
+            if let Some(seed) = options.settings.as_ref().and_then(|s| s.get_seed()) {
+                config.seed = seed;
+            }
+        }
+        None if tuning != TuningFlags::default() => {
+            return Err(CliError::Invalid(
+                "--generations, --population and --tune-matches require --tune".into(),
+            ));
+        }
+        None => {}
+    }
+
+    if options.headless {
+        let settings = options.settings.get_or_insert_with(|| {
+            let mut settings = GameSettings::default();
+            settings.update_players(1, PlayerType::Computer(default()));
+            settings
+        });
+
+        if [settings.get_player1(), settings.get_player2()]
+            .iter()
+            .any(|player| matches!(player, PlayerType::Human | PlayerType::Remote))
+        {
+            return Err(CliError::Invalid(
+                "--headless needs --p1 and --p2 to be AI or bot players".into(),
+            ));
+        }
+    }
+
+    options.matches = matches.unwrap_or(1);
+    Ok(options)
+}
+
+/// Jumps straight into the match configured on the command line.
+pub fn start_match(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
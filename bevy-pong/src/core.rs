@@ -1,7 +1,10 @@
 pub mod commands;
 pub mod controls;
+pub mod high_scores;
+pub mod rng;
 pub mod settings;
 pub mod states;
+pub mod storage;
 
 use bevy::prelude::*;
 use controls::GameControlsPlugin;
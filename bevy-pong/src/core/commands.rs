@@ -1,5 +1,9 @@
-use super::settings::{GameSettings, PlayerType};
+use super::controls::{ControlRemapping, GameAction};
+use super::settings::{GameMode, GameSettings, PaddleMovement, PlayerType};
+use super::states::GameState;
+use crate::pong::{Score, ScoreField};
 use bevy::prelude::*;
+use leafwing_input_manager::prelude::InputMap;
 
 #[derive(Clone)]
 pub struct UpdatePlayerCommand {
@@ -23,3 +27,102 @@ impl Command for UpdatePlayerCommand {
         }
     }
 }
+
+#[derive(Clone)]
+pub struct UpdateMovementCommand {
+    movement: PaddleMovement,
+}
+
+impl UpdateMovementCommand {
+    pub fn new(movement: PaddleMovement) -> Self {
+        Self { movement }
+    }
+}
+
+impl Command for UpdateMovementCommand {
+    fn apply(self, world: &mut World) {
+        if let Some(mut settings) = world.get_resource_mut::<GameSettings>() {
+            settings.set_movement(self.movement);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UpdateModeCommand {
+    mode: GameMode,
+}
+
+impl UpdateModeCommand {
+    pub fn new(mode: GameMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl Command for UpdateModeCommand {
+    fn apply(self, world: &mut World) {
+        if let Some(mut settings) = world.get_resource_mut::<GameSettings>() {
+            settings.set_mode(self.mode);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UpdateTimeLimitCommand {
+    time_limit: Option<u32>,
+}
+
+impl UpdateTimeLimitCommand {
+    pub fn new(time_limit: Option<u32>) -> Self {
+        Self { time_limit }
+    }
+}
+
+impl Command for UpdateTimeLimitCommand {
+    fn apply(self, world: &mut World) {
+        if let Some(mut settings) = world.get_resource_mut::<GameSettings>() {
+            settings.set_time_limit(self.time_limit);
+        }
+    }
+}
+
+/// Ends the running match as a loss for the paddle defending `side`.
+#[derive(Clone)]
+pub struct ForfeitCommand {
+    side: ScoreField,
+}
+
+impl ForfeitCommand {
+    pub fn new(side: ScoreField) -> Self {
+        Self { side }
+    }
+}
+
+impl Command for ForfeitCommand {
+    fn apply(self, world: &mut World) {
+        world.resource_mut::<Score>().forfeit(self.side);
+        world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Endgame);
+    }
+}
+
+/// Answers the swap/cancel prompt shown when a remapped input is already in use.
+#[derive(Clone)]
+pub struct ResolveConflictCommand {
+    swap: bool,
+}
+
+impl ResolveConflictCommand {
+    pub fn new(swap: bool) -> Self {
+        Self { swap }
+    }
+}
+
+impl Command for ResolveConflictCommand {
+    fn apply(self, world: &mut World) {
+        world.resource_scope(|world, mut remapping: Mut<ControlRemapping>| {
+            let mut input_map = world.resource_mut::<InputMap<GameAction>>();
+            remapping.resolve_conflict(&mut input_map, self.swap);
+        });
+    }
+}
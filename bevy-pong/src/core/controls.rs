@@ -1,5 +1,16 @@
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::user_input::{
+    GamepadControlAxis, GamepadControlDirection, SpecificGamepadButton,
+};
+use std::fmt;
+
+/// Number of inputs (primary and secondary) each action can be bound to.
+pub const BINDING_SLOTS: usize = 2;
+/// Seconds to wait for an input before remapping is abandoned.
+pub const LISTEN_TIMEOUT: f32 = 5.;
+/// Abandons remapping instead of being bound.
+pub const CANCEL_KEY: KeyCode = KeyCode::Escape;
 
 #[derive(Actionlike, Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect)]
 pub enum GameAction {
@@ -7,39 +18,267 @@ pub enum GameAction {
     Player1Down,
     Player2Up,
     Player2Down,
+    Player1Dash,
+    Player2Dash,
     Menu,
 }
 
 impl GameAction {
-    fn default_input_map() -> InputMap<GameAction> {
+    pub const ALL: [GameAction; 7] = [
+        GameAction::Player1Up,
+        GameAction::Player1Down,
+        GameAction::Player2Up,
+        GameAction::Player2Down,
+        GameAction::Player1Dash,
+        GameAction::Player2Dash,
+        GameAction::Menu,
+    ];
+
+    /// Actions that steer player 1's or player 2's paddle.
+    pub fn player_actions(player_num: usize) -> [GameAction; 3] {
+        if player_num == 1 {
+            [Self::Player1Up, Self::Player1Down, Self::Player1Dash]
+        } else {
+            [Self::Player2Up, Self::Player2Down, Self::Player2Dash]
+        }
+    }
+
+    pub fn default_input_map() -> InputMap<GameAction> {
         let mut input_map = InputMap::default();
         input_map.insert(Self::Player1Up, KeyCode::ArrowUp);
         input_map.insert(Self::Player1Down, KeyCode::ArrowDown);
         input_map.insert(Self::Player2Up, KeyCode::KeyW);
         input_map.insert(Self::Player2Down, KeyCode::KeyS);
+        input_map.insert(Self::Player1Dash, KeyCode::ShiftRight);
+        input_map.insert(Self::Player2Dash, KeyCode::ShiftLeft);
         input_map.insert(Self::Menu, KeyCode::Escape);
 
         input_map
     }
 }
 
+/// How a human player steers their paddle.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlScheme {
+    #[default]
+    Keyboard,
+    /// Follows the mouse cursor, or a touch on the player's half of the screen.
+    Pointer,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub struct ControlSchemes {
+    pub player1: ControlScheme,
+    pub player2: ControlScheme,
+}
+
+/// An input that can be assigned from the controls menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    fn from_input(input: &dyn Buttonlike) -> Option<Self> {
+        let input = input.as_reflect();
+        if let Some(key) = input.downcast_ref::<KeyCode>() {
+            Some(Binding::Key(*key))
+        } else {
+            input
+                .downcast_ref::<MouseButton>()
+                .map(|button| Binding::Mouse(*button))
+        }
+    }
+
+    fn insert_into(self, input_map: &mut InputMap<GameAction>, action: GameAction) {
+        match self {
+            Binding::Key(key) => input_map.insert(action, key),
+            Binding::Mouse(button) => input_map.insert(action, button),
+        };
+    }
+
+    fn boxed(self) -> Box<dyn Buttonlike> {
+        match self {
+            Binding::Key(key) => Box::new(key),
+            Binding::Mouse(button) => Box::new(button),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+        }
+    }
+}
+
+/// Bindings of `action` in slot order, primary first.
+pub fn bindings(input_map: &InputMap<GameAction>, action: GameAction) -> Vec<Binding> {
+    input_map
+        .get_buttonlike(&action)
+        .map(|inputs| {
+            inputs
+                .iter()
+                .filter_map(|input| Binding::from_input(input.as_ref()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether any of `actions` is read from `gamepad`, either through a button of that
+/// gamepad or through gamepad inputs of a map that follows it.
+pub fn uses_gamepad(
+    input_map: &InputMap<GameAction>,
+    actions: &[GameAction],
+    gamepad: Entity,
+) -> bool {
+    let follows = input_map
+        .gamepad()
+        .is_none_or(|associated| associated == gamepad);
+
+    actions.iter().any(|action| {
+        let buttons = input_map.get_buttonlike(action).into_iter().flatten();
+        let axes = input_map.get_axislike(action).into_iter().flatten();
+
+        buttons
+            .map(|input| input.as_ref().as_reflect())
+            .any(
+                |input| match input.downcast_ref::<SpecificGamepadButton>() {
+                    Some(specific) => specific.gamepad == gamepad,
+                    None => {
+                        follows
+                            && (input.is::<GamepadButton>()
+                                || input.is::<GamepadControlDirection>())
+                    }
+                },
+            )
+            || axes
+                .map(|input| input.as_ref().as_reflect())
+                .any(|input| follows && input.is::<GamepadControlAxis>())
+    })
+}
+
+/// Puts `binding` in `slot` of `action`, or clears the slot. Inputs of the action that
+/// don't fill slots, such as gamepad buttons, are kept as they are.
+fn set_binding(
+    input_map: &mut InputMap<GameAction>,
+    action: GameAction,
+    slot: usize,
+    binding: Option<Binding>,
+) {
+    let index = input_map.get_buttonlike(&action).and_then(|inputs| {
+        inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| Binding::from_input(input.as_ref()).is_some())
+            .nth(slot)
+            .map(|(index, _)| index)
+    });
+    let Some(index) = index else {
+        if let Some(binding) = binding {
+            binding.insert_into(input_map, action);
+        }
+        return;
+    };
+
+    if let Some(inputs) = input_map.get_buttonlike_mut(&action) {
+        match binding {
+            Some(binding) => inputs[index] = binding.boxed(),
+            None => {
+                inputs.remove(index);
+            }
+        }
+    }
+}
+
+/// Where an input that was pressed while remapping is already in use.
+#[derive(Clone, Copy)]
+pub struct BindingConflict {
+    pub binding: Binding,
+    pub action: GameAction,
+    slot: usize,
+}
+
 #[derive(Resource, Default, Clone, Copy)]
 pub struct ControlRemapping {
     current_action: Option<GameAction>,
+    slot: usize,
     is_listening: bool,
+    remaining: f32,
+    conflict: Option<BindingConflict>,
 }
 
 impl ControlRemapping {
-    pub fn start_remapping(control: GameAction) -> Self {
+    pub fn start_remapping(control: GameAction, slot: usize) -> Self {
         Self {
             current_action: Some(control),
+            slot,
             is_listening: true,
+            remaining: LISTEN_TIMEOUT,
+            conflict: None,
         }
     }
 
+    pub fn is_listening(&self) -> bool {
+        self.is_listening
+    }
+
+    /// Seconds left to press an input, if listening for `control` in `slot`.
+    pub fn listening_for(&self, control: GameAction, slot: usize) -> Option<f32> {
+        (self.is_listening && self.current_action == Some(control) && self.slot == slot)
+            .then_some(self.remaining)
+    }
+
+    pub fn conflict(&self) -> Option<BindingConflict> {
+        self.conflict
+    }
+
     pub fn stop_remapping(&mut self) {
         self.current_action = None;
         self.is_listening = false;
+        self.conflict = None;
+    }
+
+    /// Settles a pending conflict: swapping gives the other action the binding being
+    /// replaced, otherwise nothing changes.
+    pub fn resolve_conflict(&mut self, input_map: &mut InputMap<GameAction>, swap: bool) {
+        if let (true, Some(conflict), Some(action)) = (swap, self.conflict, self.current_action) {
+            let replaced = bindings(input_map, action).get(self.slot).copied();
+            set_binding(input_map, conflict.action, conflict.slot, replaced);
+            set_binding(input_map, action, self.slot, Some(conflict.binding));
+        }
+
+        self.stop_remapping();
+    }
+
+    fn bind(&mut self, input_map: &mut InputMap<GameAction>, action: GameAction, binding: Binding) {
+        self.is_listening = false;
+
+        let taken = GameAction::ALL.into_iter().find_map(|other| {
+            bindings(input_map, other)
+                .iter()
+                .position(|existing| *existing == binding)
+                .map(|slot| (other, slot))
+        });
+
+        match taken {
+            // Already bound to this action, in this slot or its other one.
+            Some((other, _)) if other == action => self.stop_remapping(),
+            Some((other, slot)) => {
+                self.conflict = Some(BindingConflict {
+                    binding,
+                    action: other,
+                    slot,
+                })
+            }
+            None => {
+                set_binding(input_map, action, self.slot, Some(binding));
+                self.stop_remapping();
+            }
+        }
     }
 }
 
@@ -47,16 +286,35 @@ pub fn listen_for_keys(
     mut mapping: ResMut<ControlRemapping>,
     mut key_map: ResMut<InputMap<GameAction>>,
     keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
 ) {
-    if mapping.is_listening && mapping.current_action.is_some() {
-        if let Some(control) = mapping.current_action {
-            for key in keys.get_pressed() {
-                key_map.clear_action(&control);
-                key_map.insert(control, *key);
+    if !mapping.is_listening {
+        return;
+    }
+    let Some(control) = mapping.current_action else {
+        return;
+    };
 
-                mapping.stop_remapping();
-            }
-        }
+    mapping.remaining -= time.delta_secs();
+    if mapping.remaining <= 0. || keys.just_pressed(CANCEL_KEY) {
+        mapping.stop_remapping();
+        return;
+    }
+
+    let pressed = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        });
+
+    if let Some(binding) = pressed {
+        mapping.bind(&mut key_map, control, binding);
     }
 }
 
@@ -66,6 +324,7 @@ impl Plugin for GameControlsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<GameAction>::default())
             .init_resource::<ControlRemapping>()
+            .init_resource::<ControlSchemes>()
             .init_resource::<ActionState<GameAction>>()
             .init_resource::<InputMap<GameAction>>()
             .insert_resource(GameAction::default_input_map());
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::storage;
+
+const STORAGE_NAME: &str = "high_scores";
+/// Number of runs kept in the table.
+pub const MAX_ENTRIES: usize = 10;
+
+/// A finished survival run.
+#[derive(Serialize, Deserialize, PartialEq, Copy, Clone, Debug)]
+pub struct HighScore {
+    pub points: u32,
+    pub returns: u32,
+    /// Opponent level reached when the run ended.
+    pub level: f32,
+}
+
+/// Best survival runs, highest points first; returns break ties.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Adds `score` to the table, returning its rank from 0 if it made the cut.
+    pub fn record(&mut self, score: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|entry| (score.points, score.returns) > (entry.points, entry.returns))
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.entries.insert(rank, score);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+impl storage::Persistent for HighScores {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
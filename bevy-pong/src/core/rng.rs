@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Independent random sequences, so that e.g. extra AI decisions never shift
+/// the serve angles drawn from the same seed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RngStream {
+    Serve,
+    Ai,
+}
+
+impl RngStream {
+    const ALL: [RngStream; 2] = [RngStream::Serve, RngStream::Ai];
+}
+
+/// Central source of match randomness, seeded from [`GameSettings`](super::settings::GameSettings).
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: Vec<ChaCha8Rng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = RngStream::ALL
+            .iter()
+            .map(|stream| {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                rng.set_stream(*stream as u64);
+                rng
+            })
+            .collect();
+
+        Self { seed, streams }
+    }
+
+    /// Seeds from the operating system when no seed was configured.
+    pub fn from_optional_seed(seed: Option<u64>) -> Self {
+        Self::new(seed.unwrap_or_else(rand::random))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
@@ -1,9 +1,25 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::storage;
+use crate::pong::constants::{ball, game::MAX_SCORE, paddle};
+
+const PRACTICE_STORAGE_NAME: &str = "practice";
+const SURVIVAL_STORAGE_NAME: &str = "survival";
+const AI_PRESETS_STORAGE_NAME: &str = "ai_presets";
 
 #[derive(Resource)]
 pub struct GameSettings {
     player1: PlayerType,
     player2: PlayerType,
+    points_to_win: u32,
+    arena: Arena,
+    seed: Option<u64>,
+    movement: PaddleMovement,
+    mode: GameMode,
+    time_limit: Option<u32>,
 }
 
 impl GameSettings {
@@ -15,6 +31,57 @@ impl GameSettings {
         &self.player2
     }
 
+    pub fn get_points_to_win(&self) -> u32 {
+        self.points_to_win
+    }
+
+    pub fn get_arena(&self) -> Arena {
+        self.arena
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn get_movement(&self) -> PaddleMovement {
+        self.movement
+    }
+
+    pub fn get_mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Match length in seconds, or `None` to play to the points limit.
+    pub fn get_time_limit(&self) -> Option<u32> {
+        self.time_limit
+    }
+
+    /// Time limit the current mode is played with; only matches run against the clock.
+    pub fn mode_time_limit(&self) -> Option<u32> {
+        match self.mode {
+            GameMode::Practice | GameMode::Survival => None,
+            GameMode::Match => self.time_limit,
+        }
+    }
+
+    /// Short description of the mode, length and arena, such as "first to 5, classic arena".
+    pub fn describe_rules(&self) -> String {
+        let arena = self.arena;
+        match (self.mode, self.time_limit) {
+            (GameMode::Practice, _) => format!("practice, {} arena", arena),
+            (GameMode::Survival, _) => format!("survival, {} arena", arena),
+            (GameMode::Match, Some(seconds)) => format!(
+                "{}:{:02} timed, {} arena",
+                seconds / 60,
+                seconds % 60,
+                arena
+            ),
+            (GameMode::Match, None) => {
+                format!("first to {}, {} arena", self.points_to_win, arena)
+            }
+        }
+    }
+
     pub fn update_players(&mut self, player_num: usize, player_type: PlayerType) {
         match player_num {
             1 => self.player1 = player_type,
@@ -22,6 +89,30 @@ impl GameSettings {
             _ => panic!("Invalid player num {}", player_num),
         }
     }
+
+    pub fn set_points_to_win(&mut self, points_to_win: u32) {
+        self.points_to_win = points_to_win;
+    }
+
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.arena = arena;
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn set_movement(&mut self, movement: PaddleMovement) {
+        self.movement = movement;
+    }
+
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Option<u32>) {
+        self.time_limit = time_limit;
+    }
 }
 
 impl Default for GameSettings {
@@ -29,6 +120,129 @@ impl Default for GameSettings {
         Self {
             player1: PlayerType::Human,
             player2: PlayerType::Computer(Difficulty::Easy),
+            points_to_win: MAX_SCORE,
+            arena: Arena::default(),
+            seed: None,
+            movement: PaddleMovement::default(),
+            mode: GameMode::default(),
+            time_limit: None,
+        }
+    }
+}
+
+#[derive(Default, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum GameMode {
+    #[default]
+    Match,
+    /// Player 1 drills returns against [`PracticeSettings`] instead of an opponent.
+    Practice,
+    /// Player 1 faces an AI that improves after every point they win, until they
+    /// run out of [`SurvivalSettings::lives`].
+    Survival,
+}
+
+/// What the player faces on the right side in practice mode.
+#[derive(Default, PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum PracticeOpponent {
+    /// Returned balls rebound and come straight back.
+    #[default]
+    Wall,
+    /// Balls are launched at a fixed interval and returned balls are absorbed.
+    Launcher,
+}
+
+#[derive(Resource, Serialize, Deserialize, PartialEq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct PracticeSettings {
+    pub opponent: PracticeOpponent,
+    pub serve_speed: f32,
+    /// Largest serve angle from the horizontal, in degrees.
+    pub angle_range: f32,
+    /// Vertical acceleration curving served balls until they are returned.
+    pub spin: f32,
+    /// Seconds between launches.
+    pub interval: f32,
+}
+
+impl PracticeSettings {
+    pub fn load() -> Self {
+        storage::load(PRACTICE_STORAGE_NAME)
+    }
+}
+
+impl Default for PracticeSettings {
+    fn default() -> Self {
+        Self {
+            opponent: PracticeOpponent::default(),
+            serve_speed: ball::SERVE_SPEED * 2.,
+            angle_range: 30.,
+            spin: 0.,
+            interval: 2.,
+        }
+    }
+}
+
+impl storage::Persistent for PracticeSettings {
+    const STORAGE_NAME: &'static str = PRACTICE_STORAGE_NAME;
+}
+
+#[derive(Resource, Serialize, Deserialize, PartialEq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct SurvivalSettings {
+    /// Points the player may lose before the run ends.
+    pub lives: u32,
+}
+
+impl SurvivalSettings {
+    pub fn load() -> Self {
+        storage::load(SURVIVAL_STORAGE_NAME)
+    }
+}
+
+impl Default for SurvivalSettings {
+    fn default() -> Self {
+        Self { lives: 1 }
+    }
+}
+
+impl storage::Persistent for SurvivalSettings {
+    const STORAGE_NAME: &'static str = SURVIVAL_STORAGE_NAME;
+}
+
+/// Optional paddle movement model where paddles speed up and slow down gradually
+/// and can dash; speeds are in pixels per tick.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct PaddleMovement {
+    pub momentum: bool,
+    pub acceleration: f32,
+    pub deceleration: f32,
+    pub max_speed: f32,
+    pub dash_speed: f32,
+    pub dash_ticks: u32,
+    pub dash_cooldown: u32,
+}
+
+impl PaddleMovement {
+    /// Top speed of a human paddle; AI paddles scale it by their difficulty.
+    pub fn top_speed(&self) -> f32 {
+        if self.momentum {
+            self.max_speed
+        } else {
+            paddle::SPEED
+        }
+    }
+}
+
+impl Default for PaddleMovement {
+    fn default() -> Self {
+        Self {
+            momentum: false,
+            acceleration: paddle::ACCELERATION,
+            deceleration: paddle::DECELERATION,
+            max_speed: paddle::SPEED,
+            dash_speed: paddle::DASH_SPEED,
+            dash_ticks: paddle::DASH_TICKS,
+            dash_cooldown: paddle::DASH_COOLDOWN_TICKS,
         }
     }
 }
@@ -37,6 +251,41 @@ impl Default for GameSettings {
 pub enum PlayerType {
     Human,
     Computer(Difficulty),
+    /// Moved by code outside the game through [`ExternalInput`](crate::pong::ExternalInput),
+    /// such as a [bot](crate::bot) or a [`PongEnv`](crate::env::PongEnv) agent.
+    External,
+    /// An open side for a player on another machine to join through the
+    /// [spectator stream](crate::spectator), also moved through `ExternalInput`.
+    Remote,
+}
+
+impl fmt::Display for PlayerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerType::Human => write!(f, "human"),
+            PlayerType::Computer(difficulty) => write!(f, "ai:{}", difficulty),
+            PlayerType::External => write!(f, "bot"),
+            PlayerType::Remote => write!(f, "lan"),
+        }
+    }
+}
+
+impl FromStr for PlayerType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "human" => Ok(PlayerType::Human),
+            None if s == "bot" => Ok(PlayerType::External),
+            None if s == "lan" => Ok(PlayerType::Remote),
+            None if s == "ai" => Ok(PlayerType::Computer(Difficulty::default())),
+            Some(("ai", difficulty)) => Ok(PlayerType::Computer(difficulty.parse()?)),
+            _ => Err(format!(
+                "invalid player '{}', expected human, bot, lan or ai[:difficulty]",
+                s
+            )),
+        }
+    }
 }
 
 #[derive(Default, PartialEq, Copy, Clone)]
@@ -45,14 +294,254 @@ pub enum Difficulty {
     Easy,
     Difficult,
     Impossible,
+    /// Continuous skill where 0, 1 and 2 match the named difficulties; higher
+    /// levels keep getting faster.
+    Level(f32),
+    /// Explicit parameters, as tried out by the tuner.
+    Custom(AiProfile),
 }
 
 impl Difficulty {
-    pub fn speed(&self) -> f32 {
+    /// Position on the easy (0) to impossible (2) scale, if the difficulty has one.
+    pub fn level(&self) -> Option<f32> {
         match self {
-            Difficulty::Easy => 8.,
-            Difficulty::Difficult => 10.,
-            Difficulty::Impossible => 12.,
+            Difficulty::Easy => Some(0.),
+            Difficulty::Difficult => Some(1.),
+            Difficulty::Impossible => Some(2.),
+            Difficulty::Level(level) => Some(level.max(0.)),
+            Difficulty::Custom(_) => None,
+        }
+    }
+}
+
+/// Parameters of a computer paddle.
+#[derive(Serialize, Deserialize, PartialEq, Copy, Clone, Debug)]
+pub struct AiProfile {
+    /// Top speed in pixels per tick, before paddle momentum scaling.
+    pub speed: f32,
+    /// Ticks between the ball moving and the paddle noticing.
+    pub reaction_ticks: u32,
+    /// Largest distance from the ball the paddle may aim at, rerolled every hit.
+    pub prediction_noise: f32,
+    /// From 0 to 1, how far off centre the paddle tries to hit the ball to send it
+    /// away from the opponent.
+    pub aggression: f32,
+}
+
+/// Reads the `SPEED,REACTION_TICKS,PREDICTION_NOISE,AGGRESSION` form used by
+/// custom difficulties.
+impl FromStr for AiProfile {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let mut next = || parts.next().ok_or(());
+        let profile = Self {
+            speed: next()?.parse().map_err(|_| ())?,
+            reaction_ticks: next()?.parse().map_err(|_| ())?,
+            prediction_noise: next()?.parse().map_err(|_| ())?,
+            aggression: next()?.parse().map_err(|_| ())?,
+        };
+        if parts.next().is_some() || !profile.is_valid() {
+            return Err(());
+        }
+        Ok(profile)
+    }
+}
+
+impl AiProfile {
+    /// Whether every parameter is a finite value in its range.
+    pub fn is_valid(&self) -> bool {
+        self.speed.is_finite()
+            && self.speed >= 0.
+            && self.prediction_noise.is_finite()
+            && self.prediction_noise >= 0.
+            && (0. ..=1.).contains(&self.aggression)
+    }
+
+    /// This profile with every parameter brought into its range, taking the ones that
+    /// aren't numbers from `fallback`.
+    pub fn clamped(&self, fallback: &Self) -> Self {
+        let finite = |value: f32, fallback: f32| {
+            if value.is_finite() {
+                value
+            } else {
+                fallback
+            }
+        };
+        Self {
+            speed: finite(self.speed, fallback.speed).max(0.),
+            reaction_ticks: self.reaction_ticks,
+            prediction_noise: finite(self.prediction_noise, fallback.prediction_noise).max(0.),
+            aggression: finite(self.aggression, fallback.aggression).clamp(0., 1.),
+        }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            speed: lerp(self.speed, other.speed),
+            reaction_ticks: lerp(self.reaction_ticks as f32, other.reaction_ticks as f32).round()
+                as u32,
+            prediction_noise: lerp(self.prediction_noise, other.prediction_noise).max(0.),
+            aggression: lerp(self.aggression, other.aggression).clamp(0., 1.),
+        }
+    }
+}
+
+/// Profiles behind the named difficulties, calibrated by the tuner and read from
+/// `ai_presets.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, PartialEq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct AiPresets {
+    pub easy: AiProfile,
+    pub difficult: AiProfile,
+    pub impossible: AiProfile,
+}
+
+impl AiPresets {
+    /// Presets from the data directory, with hand-edited values out of range clamped.
+    pub fn load() -> Self {
+        let loaded: Self = storage::load(AI_PRESETS_STORAGE_NAME);
+        let defaults = Self::default();
+        Self {
+            easy: loaded.easy.clamped(&defaults.easy),
+            difficult: loaded.difficult.clamped(&defaults.difficult),
+            impossible: loaded.impossible.clamped(&defaults.impossible),
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        storage::save(AI_PRESETS_STORAGE_NAME, self)
+    }
+
+    /// Profile for `difficulty`; levels between presets blend them, and levels past
+    /// impossible keep gaining speed.
+    pub fn profile(&self, difficulty: &Difficulty) -> AiProfile {
+        let level = match (difficulty, difficulty.level()) {
+            (Difficulty::Custom(profile), _) => return *profile,
+            (_, Some(level)) => level,
+            (_, None) => 0.,
+        };
+
+        if level <= 1. {
+            self.easy.lerp(&self.difficult, level)
+        } else if level <= 2. {
+            self.difficult.lerp(&self.impossible, level - 1.)
+        } else {
+            // A tuned impossible preset may be slower than difficult; never slow down.
+            let step = (self.impossible.speed - self.difficult.speed).max(0.);
+            AiProfile {
+                speed: self.impossible.speed + step * (level - 2.),
+                ..self.impossible
+            }
+        }
+    }
+}
+
+impl Default for AiPresets {
+    fn default() -> Self {
+        let profile = |speed, prediction_noise| AiProfile {
+            speed,
+            reaction_ticks: 0,
+            prediction_noise,
+            aggression: 0.,
+        };
+
+        Self {
+            easy: profile(8., 60.),
+            difficult: profile(10., 35.),
+            impossible: profile(12., 0.),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Difficult => write!(f, "difficult"),
+            Difficulty::Impossible => write!(f, "impossible"),
+            Difficulty::Level(level) => write!(f, "{}", level),
+            Difficulty::Custom(profile) => write!(
+                f,
+                "custom:{},{},{},{}",
+                profile.speed, profile.reaction_ticks, profile.prediction_noise, profile.aggression
+            ),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "difficult" => Ok(Difficulty::Difficult),
+            "impossible" => Ok(Difficulty::Impossible),
+            _ if s.starts_with("custom:") => s["custom:".len()..]
+                .parse()
+                .map(Difficulty::Custom)
+                .map_err(|_| {
+                    format!(
+                        "invalid custom difficulty '{}', expected custom:SPEED,REACTION,NOISE,AGGRESSION \
+                         with no negative values and aggression from 0 to 1",
+                        s
+                    )
+                }),
+            _ => match s.parse::<f32>() {
+                Ok(level) if level.is_finite() && level >= 0. => Ok(Difficulty::Level(level)),
+                _ => Err(format!(
+                    "invalid difficulty '{}', expected easy, difficult, impossible or a level from 0",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// Playing field layouts; each one fits inside the game window.
+#[derive(Default, PartialEq, Copy, Clone, Debug)]
+pub enum Arena {
+    #[default]
+    Classic,
+    Wide,
+    Compact,
+}
+
+impl Arena {
+    pub fn size(&self) -> Vec2 {
+        match self {
+            Arena::Classic => Vec2::new(1200., 1000.),
+            Arena::Wide => Vec2::new(1200., 700.),
+            Arena::Compact => Vec2::new(800., 800.),
+        }
+    }
+}
+
+impl fmt::Display for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arena::Classic => write!(f, "classic"),
+            Arena::Wide => write!(f, "wide"),
+            Arena::Compact => write!(f, "compact"),
+        }
+    }
+}
+
+impl FromStr for Arena {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Arena::Classic),
+            "wide" => Ok(Arena::Wide),
+            "compact" => Ok(Arena::Compact),
+            _ => Err(format!(
+                "invalid arena '{}', expected classic, wide or compact",
+                s
+            )),
         }
     }
 }
@@ -1,11 +1,21 @@
 use bevy::prelude::*;
 
+use crate::pong::ScoreField;
+
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GameState {
     #[default]
     Main,
     Controls,
+    Settings,
+    Accessibility,
     Startgame,
+    Practice,
+    Survival,
+    JoinLan,
+    Spectating,
+    Leaderboards,
+    Achievements,
     Playing,
     Endgame,
 }
@@ -17,15 +27,58 @@ pub enum PausedState {
     Paused,
 }
 
+/// Screen shown while the match is paused.
+#[derive(SubStates, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[source(PausedState = PausedState::Paused)]
+pub enum PauseMenu {
+    #[default]
+    Main,
+    Settings,
+    Controls,
+    Confirm(PauseChoice),
+}
+
+/// Pause menu choices that abandon the current match, so they are confirmed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PauseChoice {
+    Restart,
+    Forfeit(ScoreField),
+    MainMenu,
+}
+
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MainSet;
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ControlsSet;
 
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SettingsSet;
+
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StartGameSet;
 
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PracticeSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SurvivalSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JoinLanSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpectatingSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeaderboardsSet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessibilitySet;
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AchievementsSet;
+
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PlayingSet;
 
@@ -41,12 +94,21 @@ impl Plugin for GameStatesPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
             .init_state::<PausedState>()
+            .add_sub_state::<PauseMenu>()
             .configure_sets(
                 Update,
                 (
                     MainSet.run_if(in_state(GameState::Main)),
                     ControlsSet.run_if(in_state(GameState::Controls)),
+                    SettingsSet.run_if(in_state(GameState::Settings)),
+                    AccessibilitySet.run_if(in_state(GameState::Accessibility)),
                     StartGameSet.run_if(in_state(GameState::Startgame)),
+                    PracticeSet.run_if(in_state(GameState::Practice)),
+                    SurvivalSet.run_if(in_state(GameState::Survival)),
+                    JoinLanSet.run_if(in_state(GameState::JoinLan)),
+                    SpectatingSet.run_if(in_state(GameState::Spectating)),
+                    LeaderboardsSet.run_if(in_state(GameState::Leaderboards)),
+                    AchievementsSet.run_if(in_state(GameState::Achievements)),
                     PlayingSet
                         .run_if(in_state(GameState::Playing))
                         .run_if(in_state(PausedState::Playing)),
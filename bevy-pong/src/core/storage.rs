@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const DATA_DIR_VAR: &str = "BEVY_PONG_DATA_DIR";
+const DEFAULT_DATA_DIR: &str = "saves";
+
+/// Directory holding persisted settings, overridable with `BEVY_PONG_DATA_DIR`.
+pub fn data_dir() -> PathBuf {
+    std::env::var_os(DATA_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DATA_DIR))
+}
+
+fn file_path(name: &str) -> PathBuf {
+    data_dir().join(format!("{}.json", name))
+}
+
+/// Loads `name` from the data directory, falling back to the default value
+/// when the file is missing or cannot be parsed.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let path = file_path(name);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+
+    serde_json::from_str(&contents).unwrap_or_else(|error| {
+        warn!("Ignoring invalid {}: {}", path.display(), error);
+        T::default()
+    })
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    fs::create_dir_all(data_dir())?;
+    let contents = serde_json::to_string_pretty(value)?;
+    fs::write(file_path(name), contents)
+}
+
+/// A resource kept in its own file in the data directory.
+pub trait Persistent: Resource + Serialize {
+    /// Name of the file in the data directory, without the extension.
+    const STORAGE_NAME: &'static str;
+}
+
+/// Saves `T` whenever it changes, but not when it is first inserted.
+pub fn save_on_change<T: Persistent>(value: Res<T>) {
+    if value.is_changed() && !value.is_added() {
+        if let Err(error) = save(T::STORAGE_NAME, value.as_ref()) {
+            warn!("Could not save {}: {}", T::STORAGE_NAME, error);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+mod console;
+mod overlay;
+
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::core::states::PlayingSet;
+use console::*;
+use overlay::*;
+
+/// What the developer panel currently shows.
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    visible: bool,
+    show_colliders: bool,
+    show_ai_targets: bool,
+}
+
+/// Lets the console freeze the match and advance it one fixed tick at a time.
+#[derive(Resource, Default)]
+pub struct SimulationControl {
+    frozen: bool,
+    pending_steps: u32,
+}
+
+impl SimulationControl {
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+        self.pending_steps = 0;
+    }
+
+    pub fn resume(&mut self) {
+        self.frozen = false;
+        self.pending_steps = 0;
+    }
+
+    pub fn step(&mut self, ticks: u32) {
+        self.frozen = true;
+        self.pending_steps += ticks;
+    }
+}
+
+pub fn simulation_running(control: Res<SimulationControl>) -> bool {
+    !control.frozen || control.pending_steps > 0
+}
+
+fn consume_step(mut control: ResMut<SimulationControl>) {
+    if control.frozen && control.pending_steps > 0 {
+        control.pending_steps -= 1;
+    }
+}
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FrameTimeDiagnosticsPlugin,
+            RapierDebugRenderPlugin::default().disabled(),
+        ))
+        .init_resource::<DebugOverlay>()
+        .init_resource::<SimulationControl>()
+        .init_resource::<Console>()
+        .configure_sets(FixedUpdate, PlayingSet.run_if(simulation_running))
+        .configure_sets(
+            FixedUpdate,
+            PhysicsSet::StepSimulation.run_if(simulation_running),
+        )
+        .add_systems(FixedLast, consume_step)
+        .add_systems(
+            Update,
+            (
+                toggle_overlay,
+                (debug_panel, run_console_commands)
+                    .chain()
+                    .run_if(overlay_visible),
+                sync_collider_outlines,
+                draw_ai_targets.run_if(overlay_visible),
+            )
+                .chain(),
+        );
+    }
+}
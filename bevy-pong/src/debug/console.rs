@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::str::FromStr;
+
+use super::SimulationControl;
+use crate::core::rng::GameRng;
+use crate::core::settings::{Difficulty, GameSettings, PlayerType};
+use crate::pong::{serve_velocity, spawn_ball, Pong, Score, ScoreField};
+
+const MAX_LOG_LINES: usize = 100;
+const HELP: &str = "score <p1> <p2> | ball [count] | difficulty <1|2> <easy|difficult|impossible> \
+                    | freeze | resume | step [ticks] | teleport <1|2> <y>";
+
+pub enum ConsoleCommand {
+    Score(u32, u32),
+    SpawnBall(u32),
+    Difficulty(ScoreField, Difficulty),
+    Freeze,
+    Resume,
+    Step(u32),
+    Teleport(ScoreField, f32),
+    Help,
+}
+
+fn parse_player(arg: Option<&str>) -> Result<ScoreField, String> {
+    match arg {
+        Some("1") => Ok(ScoreField::Left),
+        Some("2") => Ok(ScoreField::Right),
+        other => Err(format!("expected player 1 or 2, got {:?}", other)),
+    }
+}
+
+fn parse_number<T: FromStr>(arg: Option<&str>, default: Option<T>) -> Result<T, String> {
+    match (arg, default) {
+        (Some(arg), _) => arg.parse().map_err(|_| format!("invalid number {:?}", arg)),
+        (None, Some(default)) => Ok(default),
+        (None, None) => Err("missing number".into()),
+    }
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some("score") => Self::Score(
+                parse_number(args.next(), None)?,
+                parse_number(args.next(), None)?,
+            ),
+            Some("ball") => Self::SpawnBall(parse_number(args.next(), Some(1))?),
+            Some("difficulty") => {
+                let player = parse_player(args.next())?;
+                let difficulty = args.next().unwrap_or_default().parse()?;
+                Self::Difficulty(player, difficulty)
+            }
+            Some("freeze") => Self::Freeze,
+            Some("resume") => Self::Resume,
+            Some("step") => Self::Step(parse_number(args.next(), Some(1))?),
+            Some("teleport") => {
+                Self::Teleport(parse_player(args.next())?, parse_number(args.next(), None)?)
+            }
+            Some("help") => Self::Help,
+            Some(other) => return Err(format!("unknown command {:?}", other)),
+            None => return Err("empty command".into()),
+        };
+
+        Ok(command)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    input: String,
+    log: Vec<String>,
+    pending: Vec<ConsoleCommand>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        if line.trim().is_empty() {
+            return;
+        }
+
+        self.print(format!("> {}", line));
+        match line.parse() {
+            Ok(command) => self.pending.push(command),
+            Err(error) => self.print(error),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .max_height(150.)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &self.log {
+                    ui.monospace(line);
+                }
+            });
+
+        let response = ui.text_edit_singleline(&mut self.input);
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            self.submit();
+            response.request_focus();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_console_commands(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut score: ResMut<Score>,
+    mut simulation: ResMut<SimulationControl>,
+    mut rng: ResMut<GameRng>,
+    mut settings: Option<ResMut<GameSettings>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pong: Query<Entity, With<Pong>>,
+    mut paddles: Query<(&mut PlayerType, &mut Transform, &ScoreField)>,
+) {
+    for command in std::mem::take(&mut console.pending) {
+        match command {
+            ConsoleCommand::Score(player1, player2) => score.set(player1, player2),
+            ConsoleCommand::SpawnBall(count) => {
+                let Ok(pong) = pong.get_single() else {
+                    console.print("no match running");
+                    continue;
+                };
+                commands.entity(pong).with_children(|parent| {
+                    for _ in 0..count {
+                        let serve = serve_velocity(&mut rng);
+                        spawn_ball(parent, &mut meshes, &mut materials, serve);
+                    }
+                });
+            }
+            ConsoleCommand::Difficulty(side, difficulty) => {
+                if let Some(settings) = settings.as_mut() {
+                    let player_num = if side == ScoreField::Left { 1 } else { 2 };
+                    settings.update_players(player_num, PlayerType::Computer(difficulty));
+                }
+                for (mut player_type, _, field) in paddles.iter_mut() {
+                    if *field == side {
+                        *player_type = PlayerType::Computer(difficulty);
+                    }
+                }
+            }
+            ConsoleCommand::Freeze => simulation.freeze(),
+            ConsoleCommand::Resume => simulation.resume(),
+            ConsoleCommand::Step(ticks) => simulation.step(ticks),
+            ConsoleCommand::Teleport(side, y) => {
+                for (_, mut transform, field) in paddles.iter_mut() {
+                    if *field == side {
+                        transform.translation.y = y;
+                    }
+                }
+            }
+            ConsoleCommand::Help => console.print(HELP),
+        }
+    }
+}
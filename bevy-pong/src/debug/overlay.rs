@@ -0,0 +1,103 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier2d::prelude::*;
+
+use super::console::Console;
+use super::{DebugOverlay, SimulationControl};
+use crate::core::settings::PlayerType;
+use crate::core::states::{GameState, PausedState};
+use crate::pong::{AiTarget, Ball, Score};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const AI_TARGET_RADIUS: f32 = 6.;
+
+pub fn overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+pub fn toggle_overlay(keys: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn debug_panel(
+    mut contexts: EguiContexts,
+    mut overlay: ResMut<DebugOverlay>,
+    mut console: ResMut<Console>,
+    diagnostics: Res<DiagnosticsStore>,
+    game_state: Res<State<GameState>>,
+    paused_state: Res<State<PausedState>>,
+    simulation: Res<SimulationControl>,
+    score: Res<Score>,
+    balls: Query<&Velocity, With<Ball>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+
+    egui::Window::new("Debug")
+        .default_pos([10., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!("FPS: {:.0}", fps));
+            ui.label(format!("GameState: {:?}", game_state.get()));
+            ui.label(format!("PausedState: {:?}", paused_state.get()));
+            ui.label(format!("Score: {}", score.display_text()));
+            ui.label(format!(
+                "Simulation: {}",
+                if simulation.frozen {
+                    "frozen"
+                } else {
+                    "running"
+                }
+            ));
+
+            for (index, velocity) in balls.iter().enumerate() {
+                ui.label(format!(
+                    "Ball {}: ({:.1}, {:.1}) |{:.1}|",
+                    index,
+                    velocity.linvel.x,
+                    velocity.linvel.y,
+                    velocity.linvel.length()
+                ));
+            }
+
+            ui.checkbox(&mut overlay.show_colliders, "Collider outlines");
+            ui.checkbox(&mut overlay.show_ai_targets, "AI targets");
+
+            ui.separator();
+            console.show(ui);
+        });
+}
+
+pub fn sync_collider_outlines(
+    overlay: Res<DebugOverlay>,
+    mut debug_render: ResMut<DebugRenderContext>,
+) {
+    if overlay.is_changed() {
+        debug_render.enabled = overlay.visible && overlay.show_colliders;
+    }
+}
+
+pub fn draw_ai_targets(
+    overlay: Res<DebugOverlay>,
+    mut gizmos: Gizmos,
+    paddles: Query<(&AiTarget, &PlayerType)>,
+) {
+    if !overlay.show_ai_targets {
+        return;
+    }
+
+    for (target, player_type) in paddles.iter() {
+        if matches!(player_type, PlayerType::Computer(_)) {
+            gizmos.circle_2d(
+                Isometry2d::from_translation(target.0),
+                AI_TARGET_RADIUS,
+                Color::srgb(1., 0.3, 0.3),
+            );
+        }
+    }
+}
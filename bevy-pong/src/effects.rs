@@ -0,0 +1,364 @@
+//! Visual feedback during a match: ball trails, impact sparks, a flash on the goal
+//! side and a pulse on the score.
+//!
+//! Every effect is a short-lived mesh parented under [`Pong`], so it goes away with
+//! the rest of the arena. Effects draw their randomness from the thread RNG rather
+//! than [`GameRng`](crate::core::rng::GameRng), keeping seeded matches reproducible.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+use crate::accessibility::AccessibilitySettings;
+use crate::core::settings::{GameSettings, PlayerType};
+use crate::core::states::PlayingSet;
+use crate::core::storage;
+use crate::pong::constants::ball::{MAX_BALL_SPEED, RADIUS};
+use crate::pong::constants::TOP_BUFFER;
+use crate::pong::{Ball, OnPaddleHit, OnPointScored, OnWallBounce, Pong, ScoreField, ScoreText};
+use crate::theme::Theme;
+
+const STORAGE_NAME: &str = "effects";
+
+/// Seconds a trail lasts behind a ball at top speed.
+const TRAIL_LIFETIME: f32 = 0.25;
+const TRAIL_ALPHA: f32 = 0.5;
+const SPARK_LIFETIME: f32 = 0.35;
+const SPARK_SIZE: f32 = 4.;
+/// Sparks thrown by an impact at top speed.
+const MAX_SPARKS: usize = 16;
+const FLASH_LIFETIME: f32 = 0.4;
+const FLASH_ALPHA: f32 = 0.35;
+const PULSE_SECONDS: f32 = 0.3;
+/// Extra scale the score text reaches at the height of its pulse.
+const PULSE_SCALE: f32 = 0.3;
+
+/// Saved to `effects.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct EffectsSettings {
+    /// Drops trails, the score pulse and camera shake, throws fewer sparks and softens
+    /// the goal flash.
+    pub reduced_motion: bool,
+}
+
+impl EffectsSettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    fn sparks(&self, speed: f32) -> usize {
+        let sparks = ((speed / MAX_BALL_SPEED).clamp(0.25, 1.) * MAX_SPARKS as f32) as usize;
+        if self.reduced_motion {
+            sparks / 4
+        } else {
+            sparks
+        }
+    }
+
+    fn flash_alpha(&self) -> f32 {
+        if self.reduced_motion {
+            FLASH_ALPHA / 3.
+        } else {
+            FLASH_ALPHA
+        }
+    }
+}
+
+/// Meshes shared by every effect; each effect gets its own material to fade.
+#[derive(Resource)]
+struct EffectMeshes {
+    circle: Handle<Mesh>,
+    square: Handle<Mesh>,
+}
+
+/// Fades an effect out over its lifetime, optionally shrinking it from `shrink`, then
+/// despawns it.
+#[derive(Component)]
+struct Fade {
+    timer: Timer,
+    alpha: f32,
+    shrink: Option<Vec3>,
+}
+
+impl Fade {
+    fn new(seconds: f32, alpha: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            alpha,
+            shrink: None,
+        }
+    }
+
+    fn shrinking(mut self, scale: Vec3) -> Self {
+        self.shrink = Some(scale);
+        self
+    }
+}
+
+/// Velocity of a spark, in units per second.
+#[derive(Component)]
+struct Drift(Vec2);
+
+/// Briefly scales up the score text.
+#[derive(Component)]
+struct Pulse(Timer);
+
+fn setup_effect_meshes(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(EffectMeshes {
+        circle: meshes.add(Circle::new(RADIUS)),
+        square: meshes.add(Rectangle::new(1., 1.)),
+    });
+}
+
+fn spawn_effect(
+    commands: &mut Commands,
+    pong: Entity,
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+    transform: Transform,
+    extra: impl Bundle,
+) {
+    commands.entity(pong).with_children(|parent| {
+        parent.spawn((Mesh2d(mesh), MeshMaterial2d(material), transform, extra));
+    });
+}
+
+/// Leaves a fading copy of each moving ball behind it, lasting longer the faster it goes.
+fn spawn_trails(
+    mut commands: Commands,
+    settings: Res<EffectsSettings>,
+    meshes: Res<EffectMeshes>,
+    theme: Res<Theme>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pong: Query<Entity, With<Pong>>,
+    balls: Query<(&Transform, &Velocity), With<Ball>>,
+) {
+    let Ok(pong) = pong.get_single() else {
+        return;
+    };
+    if settings.reduced_motion {
+        return;
+    }
+
+    for (transform, velocity) in &balls {
+        let lifetime = TRAIL_LIFETIME * (velocity.linvel.length() / MAX_BALL_SPEED).min(1.);
+        if lifetime <= 0. {
+            continue;
+        }
+        let color = theme.ball.0.with_alpha(TRAIL_ALPHA);
+        spawn_effect(
+            &mut commands,
+            pong,
+            meshes.circle.clone(),
+            materials.add(color),
+            Transform::from_translation(transform.translation.with_z(-0.1)),
+            Fade::new(lifetime, TRAIL_ALPHA).shrinking(Vec3::ONE),
+        );
+    }
+}
+
+fn spawn_sparks(
+    commands: &mut Commands,
+    pong: Entity,
+    meshes: &EffectMeshes,
+    materials: &mut Assets<ColorMaterial>,
+    color: Color,
+    position: Vec2,
+    count: usize,
+) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let angle = rng.gen_range(0.0..TAU);
+        let speed = rng.gen_range(80.0..260.0);
+        spawn_effect(
+            commands,
+            pong,
+            meshes.square.clone(),
+            materials.add(color),
+            Transform::from_translation(position.extend(0.1)).with_scale(Vec3::splat(SPARK_SIZE)),
+            (
+                Fade::new(SPARK_LIFETIME, 1.).shrinking(Vec3::splat(SPARK_SIZE)),
+                Drift(Vec2::from_angle(angle) * speed),
+            ),
+        );
+    }
+}
+
+fn paddle_sparks(
+    trigger: Trigger<OnPaddleHit>,
+    mut commands: Commands,
+    settings: Res<EffectsSettings>,
+    meshes: Res<EffectMeshes>,
+    theme: Res<Theme>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pong: Query<Entity, With<Pong>>,
+) {
+    let Ok(pong) = pong.get_single() else {
+        return;
+    };
+    spawn_sparks(
+        &mut commands,
+        pong,
+        &meshes,
+        &mut materials,
+        theme.paddle(trigger.side),
+        trigger.position,
+        settings.sparks(trigger.speed),
+    );
+}
+
+fn wall_sparks(
+    trigger: Trigger<OnWallBounce>,
+    mut commands: Commands,
+    settings: Res<EffectsSettings>,
+    meshes: Res<EffectMeshes>,
+    theme: Res<Theme>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pong: Query<Entity, With<Pong>>,
+) {
+    let Ok(pong) = pong.get_single() else {
+        return;
+    };
+    spawn_sparks(
+        &mut commands,
+        pong,
+        &meshes,
+        &mut materials,
+        theme.walls.0,
+        trigger.position,
+        settings.sparks(trigger.speed) / 2,
+    );
+}
+
+/// Lights up the half of the arena whose goal was just hit.
+#[allow(clippy::too_many_arguments)]
+fn goal_flash(
+    trigger: Trigger<OnPointScored>,
+    mut commands: Commands,
+    settings: Res<EffectsSettings>,
+    accessibility: Option<Res<AccessibilitySettings>>,
+    game: Res<GameSettings>,
+    meshes: Res<EffectMeshes>,
+    theme: Res<Theme>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    pong: Query<Entity, With<Pong>>,
+    goals: Query<&ScoreField, Without<PlayerType>>,
+) {
+    if accessibility.is_some_and(|accessibility| !accessibility.flashing) {
+        return;
+    }
+    let (Ok(pong), Ok(side)) = (pong.get_single(), goals.get(trigger.0)) else {
+        return;
+    };
+
+    let size = game.get_arena().size();
+    let x = match side {
+        ScoreField::Left => -size.x / 4.,
+        ScoreField::Right => size.x / 4.,
+    };
+    let alpha = settings.flash_alpha();
+    spawn_effect(
+        &mut commands,
+        pong,
+        meshes.square.clone(),
+        materials.add(theme.text.0.with_alpha(alpha)),
+        Transform::from_xyz(x, TOP_BUFFER / -2., -0.2).with_scale(Vec3::new(
+            size.x / 2.,
+            size.y - TOP_BUFFER,
+            1.,
+        )),
+        Fade::new(FLASH_LIFETIME, alpha),
+    );
+}
+
+fn pulse_score(
+    _: Trigger<OnPointScored>,
+    mut commands: Commands,
+    settings: Res<EffectsSettings>,
+    texts: Query<Entity, With<ScoreText>>,
+) {
+    if settings.reduced_motion {
+        return;
+    }
+    for text in &texts {
+        commands
+            .entity(text)
+            .insert(Pulse(Timer::from_seconds(PULSE_SECONDS, TimerMode::Once)));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fade_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut effects: Query<(
+        Entity,
+        &mut Fade,
+        &mut Transform,
+        &MeshMaterial2d<ColorMaterial>,
+        Option<&Drift>,
+    )>,
+) {
+    for (entity, mut fade, mut transform, material, drift) in &mut effects {
+        fade.timer.tick(time.delta());
+        if fade.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = 1. - fade.timer.fraction();
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.color.set_alpha(fade.alpha * remaining);
+        }
+        if let Some(Drift(velocity)) = drift {
+            transform.translation += (*velocity * time.delta_secs()).extend(0.);
+        }
+        if let Some(scale) = fade.shrink {
+            transform.scale = (scale * remaining).with_z(1.);
+        }
+    }
+}
+
+fn animate_pulses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut texts: Query<(Entity, &mut Pulse, &mut Transform)>,
+) {
+    for (entity, mut pulse, mut transform) in &mut texts {
+        pulse.0.tick(time.delta());
+        if pulse.0.finished() {
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<Pulse>();
+        } else {
+            let scale = 1. + PULSE_SCALE * (pulse.0.fraction() * PI).sin();
+            transform.scale = Vec3::new(scale, scale, 1.);
+        }
+    }
+}
+
+impl storage::Persistent for EffectsSettings {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
+
+/// Match effects for the windowed game; needs the [`ThemePlugin`](crate::theme::ThemePlugin).
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EffectsSettings::load())
+            .add_observer(paddle_sparks)
+            .add_observer(wall_sparks)
+            .add_observer(goal_flash)
+            .add_observer(pulse_score)
+            .add_systems(Startup, setup_effect_meshes)
+            .add_systems(
+                Update,
+                (spawn_trails, fade_effects, animate_pulses).in_set(PlayingSet),
+            )
+            .add_systems(Update, storage::save_on_change::<EffectsSettings>);
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use serde::Serialize;
+
+use crate::core::settings::{Difficulty, GameSettings, PlayerType};
+use crate::core::states::GameState;
+use crate::core::GamePlugin;
+use crate::headless::SimulationPlugin;
+use crate::pong::constants::ball::MAX_BALL_SPEED;
+use crate::pong::{
+    Ball, ExternalInput, MatchClock, PongPlugin, RestartMatch, Score, ScoreField, WinConditions,
+};
+
+/// Updates allowed for a reset to bring the arena into play.
+const MAX_RESET_UPDATES: u32 = 10;
+
+/// Paddle movement chosen by an agent for one tick.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Action {
+    #[default]
+    Stay,
+    Up,
+    Down,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Stay, Action::Up, Action::Down];
+
+    pub fn direction(&self) -> f32 {
+        match self {
+            Action::Stay => 0.,
+            Action::Up => 1.,
+            Action::Down => -1.,
+        }
+    }
+}
+
+/// State of the arena after a tick, in world units with the origin at the centre.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug, Default)]
+pub struct Observation {
+    pub ball_x: f32,
+    pub ball_y: f32,
+    pub ball_vx: f32,
+    pub ball_vy: f32,
+    pub paddle1_y: f32,
+    pub paddle2_y: f32,
+    pub score1: u32,
+    pub score2: u32,
+    /// Half the arena's width and height, for scaling the positions.
+    pub half_width: f32,
+    pub half_height: f32,
+}
+
+impl Observation {
+    /// Ball and paddle state scaled to roughly -1..1, for feeding to a model.
+    pub fn features(&self) -> [f32; 6] {
+        [
+            self.ball_x / self.half_width,
+            self.ball_y / self.half_height,
+            self.ball_vx / MAX_BALL_SPEED,
+            self.ball_vy / MAX_BALL_SPEED,
+            self.paddle1_y / self.half_height,
+            self.paddle2_y / self.half_height,
+        ]
+    }
+}
+
+/// A headless match driven one fixed tick at a time, with the same rules as the game.
+///
+/// Every [`PlayerType::External`] paddle follows the actions passed to
+/// [`step`](Self::step); computer paddles ignore them. Rewards are from player 1's
+/// side: 1 when they win a point and -1 when they lose one.
+pub struct PongEnv {
+    app: App,
+    done: bool,
+}
+
+impl PongEnv {
+    pub fn new(settings: GameSettings) -> Self {
+        let mut app = App::new();
+        app.add_plugins((SimulationPlugin, GamePlugin, PongPlugin))
+            .insert_resource(settings);
+        app.finish();
+        app.cleanup();
+
+        Self { app, done: true }
+    }
+
+    /// An agent as player 1 against the computer at `difficulty`.
+    pub fn against(difficulty: Difficulty) -> Self {
+        let mut settings = GameSettings::default();
+        settings.update_players(1, PlayerType::External);
+        settings.update_players(2, PlayerType::Computer(difficulty));
+        Self::new(settings)
+    }
+
+    /// Starts a new match from 0 - 0 with `seed`.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let world = self.app.world_mut();
+        world.resource_mut::<GameSettings>().set_seed(Some(seed));
+        *world.resource_mut::<ExternalInput>() = ExternalInput::default();
+
+        if *world.resource::<State<GameState>>().get() == GameState::Playing {
+            // Drop a pending move to the end screen from the last point.
+            world.resource_mut::<NextState<GameState>>().reset();
+            world.trigger(RestartMatch);
+            world.flush();
+        } else {
+            world
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::Playing);
+        }
+
+        for _ in 0..MAX_RESET_UPDATES {
+            if self.in_play() {
+                break;
+            }
+            self.app.update();
+        }
+
+        self.done = false;
+        self.observe()
+    }
+
+    /// Applies both actions for one tick. Once the match is over it stays done
+    /// until the next [`reset`](Self::reset).
+    pub fn step(&mut self, action_p1: Action, action_p2: Action) -> (Observation, f32, bool) {
+        if self.done {
+            return (self.observe(), 0., true);
+        }
+
+        let before = self.points();
+        let world = self.app.world_mut();
+        let mut input = world.resource_mut::<ExternalInput>();
+        input.set(ScoreField::Left, action_p1.direction());
+        input.set(ScoreField::Right, action_p2.direction());
+
+        self.app.update();
+
+        let after = self.points();
+        let reward = (after.0 - before.0) as f32 - (after.1 - before.1) as f32;
+        let world = self.app.world();
+        self.done = world
+            .resource::<WinConditions>()
+            .is_met(world.resource::<Score>(), world.resource::<MatchClock>());
+
+        (self.observe(), reward, self.done)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The underlying app, for reading or adjusting game resources directly.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    fn in_play(&mut self) -> bool {
+        let world = self.app.world_mut();
+        *world.resource::<State<GameState>>().get() == GameState::Playing
+            && world
+                .query_filtered::<(), With<Ball>>()
+                .iter(world)
+                .next()
+                .is_some()
+    }
+
+    fn points(&self) -> (u32, u32) {
+        let score = self.app.world().resource::<Score>();
+        (score.player1(), score.player2())
+    }
+
+    fn observe(&mut self) -> Observation {
+        observe(self.app.world_mut())
+    }
+}
+
+/// Reads the running match into an observation.
+pub fn observe(world: &mut World) -> Observation {
+    let half_size = world.resource::<GameSettings>().get_arena().size() / 2.;
+    let score = world.resource::<Score>();
+
+    let mut observation = Observation {
+        score1: score.player1(),
+        score2: score.player2(),
+        half_width: half_size.x,
+        half_height: half_size.y,
+        ..default()
+    };
+
+    // With several balls in play the agent sees the one nearest to its goal.
+    if let Some((transform, velocity)) = world
+        .query_filtered::<(&Transform, &Velocity), With<Ball>>()
+        .iter(world)
+        .min_by(|a, b| a.0.translation.x.total_cmp(&b.0.translation.x))
+    {
+        observation.ball_x = transform.translation.x;
+        observation.ball_y = transform.translation.y;
+        observation.ball_vx = velocity.linvel.x;
+        observation.ball_vy = velocity.linvel.y;
+    }
+
+    for (transform, field) in world
+        .query_filtered::<(&Transform, &ScoreField), With<PlayerType>>()
+        .iter(world)
+    {
+        match field {
+            ScoreField::Left => observation.paddle1_y = transform.translation.y,
+            ScoreField::Right => observation.paddle2_y = transform.translation.y,
+        }
+    }
+
+    observation
+}
+
+/// A batch of environments stepped together. A finished environment is reset
+/// straight away with its seed advanced by the batch size, so the observation
+/// returned alongside `done` is the first of its next match.
+pub struct VecPongEnv {
+    envs: Vec<PongEnv>,
+    seeds: Vec<u64>,
+}
+
+impl VecPongEnv {
+    pub fn new(count: usize, settings: impl Fn() -> GameSettings) -> Self {
+        Self {
+            envs: (0..count).map(|_| PongEnv::new(settings())).collect(),
+            seeds: vec![0; count],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    /// Resets environment `i` with seed `seed + i`.
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.envs
+            .iter_mut()
+            .zip(self.seeds.iter_mut())
+            .enumerate()
+            .map(|(i, (env, env_seed))| {
+                *env_seed = seed.wrapping_add(i as u64);
+                env.reset(*env_seed)
+            })
+            .collect()
+    }
+
+    /// Steps every environment with its pair of actions, in order.
+    pub fn step(&mut self, actions: &[(Action, Action)]) -> Vec<(Observation, f32, bool)> {
+        assert_eq!(
+            actions.len(),
+            self.envs.len(),
+            "expected one pair of actions per environment"
+        );
+        let batch = self.envs.len() as u64;
+
+        self.envs
+            .iter_mut()
+            .zip(self.seeds.iter_mut())
+            .zip(actions)
+            .map(|((env, seed), (action_p1, action_p2))| {
+                let (observation, reward, done) = env.step(*action_p1, *action_p2);
+                if !done {
+                    return (observation, reward, done);
+                }
+
+                *seed = seed.wrapping_add(batch);
+                (env.reset(*seed), reward, done)
+            })
+            .collect()
+    }
+}
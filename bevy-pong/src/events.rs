@@ -0,0 +1,272 @@
+//! What happens in a match, as one typed stream of events.
+//!
+//! [`GameplayEvent`]s are triggered as the match plays out, so anything can observe
+//! them without knowing how the pong systems detect hits and points. With an
+//! [`EventLog`] in the world they are also written to a file, one JSON object per
+//! line with the time and match clock tick they happened at. Practice has no
+//! opponent or score to speak of, so nothing in it is reported.
+
+use bevy::prelude::*;
+use bevy::state::state::StateTransitionEvent;
+use bevy_rapier2d::prelude::{PhysicsSet, Velocity};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::settings::{GameMode, GameSettings, PlayerType};
+use crate::core::states::{GameState, PausedState, PlayingSet};
+use crate::pong::{
+    match_result, Ball, MatchClock, MatchResult, OnPaddleHit, OnPointScored, OnWallBounce,
+    RestartMatch, Score, ScoreField, ServeCountdown, SurvivalRun,
+};
+
+#[derive(Event, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameplayEvent {
+    /// A ball was put in play, heading for the `towards` side.
+    ServeStarted {
+        towards: ScoreField,
+    },
+    /// A paddle returned the ball; `hit_offset` runs from -1 at the bottom of the
+    /// paddle to 1 at the top.
+    PaddleHit {
+        side: ScoreField,
+        hit_offset: f32,
+        speed: f32,
+    },
+    WallBounce {
+        speed: f32,
+    },
+    /// `side` scored after a rally of `rally_len` paddle hits.
+    PointScored {
+        side: ScoreField,
+        rally_len: u32,
+    },
+    MatchPaused,
+    MatchResumed,
+    MatchEnded {
+        result: MatchResult,
+    },
+}
+
+/// One line of the event log.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct EventRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Match clock tick the event happened on.
+    pub tick: u64,
+    #[serde(flatten)]
+    pub event: GameplayEvent,
+}
+
+/// Writes every gameplay event to a JSON-lines file while it is in the world.
+#[derive(Resource)]
+pub struct EventLog(LineWriter<File>);
+
+impl EventLog {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self(LineWriter::new(File::create(path)?)))
+    }
+
+    fn write(&mut self, record: &EventRecord) -> io::Result<()> {
+        let line = serde_json::to_string(record)?;
+        writeln!(self.0, "{}", line)
+    }
+}
+
+/// Paddle hits since the current ball was served.
+#[derive(Resource, Default)]
+struct RallyLength(u32);
+
+/// Whether the running match was paused by the player, so that the pause state
+/// being reset for a new match isn't reported as a resume.
+#[derive(Resource, Default)]
+struct PausedByPlayer(bool);
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn towards(velocity: &Velocity) -> ScoreField {
+    if velocity.linvel.x < 0. {
+        ScoreField::Left
+    } else {
+        ScoreField::Right
+    }
+}
+
+/// Balls are served either already moving or held still by a countdown, in which
+/// case the serve is reported once they are released.
+fn report_serves(
+    mut commands: Commands,
+    mut rally: ResMut<RallyLength>,
+    mut released: RemovedComponents<ServeCountdown>,
+    settings: Res<GameSettings>,
+    served: Query<&Velocity, (Added<Ball>, Without<ServeCountdown>)>,
+    balls: Query<&Velocity, With<Ball>>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+
+    let released = released.read().filter_map(|ball| balls.get(ball).ok());
+    let serves: Vec<ScoreField> = served.iter().chain(released).map(towards).collect();
+    for towards in serves {
+        rally.0 = 0;
+        commands.trigger(GameplayEvent::ServeStarted { towards });
+    }
+}
+
+fn report_paddle_hit(
+    trigger: Trigger<OnPaddleHit>,
+    mut commands: Commands,
+    mut rally: ResMut<RallyLength>,
+    settings: Res<GameSettings>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+
+    rally.0 += 1;
+    commands.trigger(GameplayEvent::PaddleHit {
+        side: trigger.side,
+        hit_offset: trigger.offset,
+        speed: trigger.speed,
+    });
+}
+
+fn report_wall_bounce(
+    trigger: Trigger<OnWallBounce>,
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+
+    commands.trigger(GameplayEvent::WallBounce {
+        speed: trigger.speed,
+    });
+}
+
+/// The side whose goal the ball went into loses the point.
+fn report_point(
+    trigger: Trigger<OnPointScored>,
+    mut commands: Commands,
+    rally: Res<RallyLength>,
+    settings: Res<GameSettings>,
+    walls: Query<&ScoreField, Without<PlayerType>>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+
+    if let Ok(wall) = walls.get(trigger.0) {
+        let side = match wall {
+            ScoreField::Left => ScoreField::Right,
+            ScoreField::Right => ScoreField::Left,
+        };
+        commands.trigger(GameplayEvent::PointScored {
+            side,
+            rally_len: rally.0,
+        });
+    }
+}
+
+fn report_pauses(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<PausedState>>,
+    mut paused: ResMut<PausedByPlayer>,
+    state: Res<State<GameState>>,
+    settings: Res<GameSettings>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        transitions.clear();
+        return;
+    }
+
+    for transition in transitions.read() {
+        match transition.entered {
+            Some(PausedState::Paused) if *state.get() == GameState::Playing && !paused.0 => {
+                paused.0 = true;
+                commands.trigger(GameplayEvent::MatchPaused);
+            }
+            Some(PausedState::Playing) if paused.0 => {
+                paused.0 = false;
+                commands.trigger(GameplayEvent::MatchResumed);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn forget_pause(mut paused: ResMut<PausedByPlayer>) {
+    paused.0 = false;
+}
+
+fn forget_pause_on_restart(_: Trigger<RestartMatch>, mut paused: ResMut<PausedByPlayer>) {
+    paused.0 = false;
+}
+
+fn report_match_end(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    score: Res<Score>,
+    clock: Res<MatchClock>,
+    run: Res<SurvivalRun>,
+) {
+    if let Some(result) = match_result(&settings, &score, &clock, &run, None) {
+        commands.trigger(GameplayEvent::MatchEnded { result });
+    }
+}
+
+fn write_event(
+    trigger: Trigger<GameplayEvent>,
+    mut commands: Commands,
+    log: Option<ResMut<EventLog>>,
+    clock: Res<MatchClock>,
+) {
+    let Some(mut log) = log else {
+        return;
+    };
+    let record = EventRecord {
+        timestamp: unix_millis(),
+        tick: clock.ticks(),
+        event: trigger.event().clone(),
+    };
+    if let Err(error) = log.write(&record) {
+        warn!(
+            "Could not write gameplay events, no longer logging them: {}",
+            error
+        );
+        commands.remove_resource::<EventLog>();
+    }
+}
+
+pub struct GameplayEventsPlugin;
+
+impl Plugin for GameplayEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RallyLength>()
+            .init_resource::<PausedByPlayer>()
+            .add_observer(report_paddle_hit)
+            .add_observer(report_wall_bounce)
+            .add_observer(report_point)
+            .add_observer(forget_pause_on_restart)
+            .add_observer(write_event)
+            .add_systems(
+                FixedUpdate,
+                report_serves
+                    .in_set(PlayingSet)
+                    .after(PhysicsSet::Writeback),
+            )
+            .add_systems(Update, report_pauses)
+            .add_systems(OnExit(GameState::Playing), forget_pause)
+            .add_systems(OnEnter(GameState::Endgame), report_match_end);
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::input::InputPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::rng::GameRng;
+use crate::core::settings::GameSettings;
+use crate::core::states::GameState;
+use crate::pong::constants::TICK_RATE;
+use crate::pong::{MatchClock, Score};
+
+#[derive(Serialize)]
+struct MatchResult {
+    winner: String,
+    player1: u32,
+    player2: u32,
+    seed: u64,
+    ticks: u64,
+    seconds: f64,
+}
+
+#[derive(Resource, Serialize, Default)]
+struct MatchReport {
+    player1: String,
+    player2: String,
+    points: u32,
+    arena: String,
+    seed: Option<u64>,
+    player1_wins: u32,
+    player2_wins: u32,
+    matches: Vec<MatchResult>,
+}
+
+#[derive(Resource)]
+struct HeadlessConfig {
+    matches: u32,
+    report: Option<PathBuf>,
+}
+
+/// Windowless app core that advances exactly one fixed tick per update.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+            StatesPlugin,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+        ))
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / TICK_RATE,
+        )));
+    }
+}
+
+/// Runs matches without a window, one fixed tick per update and as fast as possible.
+pub struct HeadlessPlugin {
+    matches: u32,
+    report: Option<PathBuf>,
+}
+
+impl HeadlessPlugin {
+    pub fn new(matches: u32, report: Option<PathBuf>) -> Self {
+        Self { matches, report }
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((SimulationPlugin, LogPlugin::default()))
+            .insert_resource(HeadlessConfig {
+                matches: self.matches,
+                report: self.report.clone(),
+            })
+            .init_resource::<MatchReport>()
+            .add_systems(OnEnter(GameState::Endgame), record_match);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_match(
+    mut commands: Commands,
+    mut report: ResMut<MatchReport>,
+    mut next_state: ResMut<NextState<GameState>>,
+    config: Res<HeadlessConfig>,
+    mut settings: ResMut<GameSettings>,
+    score: Res<Score>,
+    clock: Res<MatchClock>,
+    rng: Res<GameRng>,
+) {
+    let winner = score.get_winner();
+    if score.player1() > score.player2() {
+        report.player1_wins += 1;
+    } else {
+        report.player2_wins += 1;
+    }
+
+    info!(
+        "Match {}/{}: {} wins {}",
+        report.matches.len() + 1,
+        config.matches,
+        winner,
+        score.display_text()
+    );
+    if report.matches.is_empty() {
+        report.seed = settings.get_seed();
+    }
+    report.matches.push(MatchResult {
+        winner,
+        seed: rng.seed(),
+        player1: score.player1(),
+        player2: score.player2(),
+        ticks: clock.ticks(),
+        seconds: clock.seconds(),
+    });
+
+    if report.matches.len() < config.matches as usize {
+        // Each following match gets the next seed, so any match can be replayed
+        // on its own by passing its recorded seed.
+        if let Some(seed) = settings.get_seed() {
+            settings.set_seed(Some(seed.wrapping_add(1)));
+        }
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    report.player1 = settings.get_player1().to_string();
+    report.player2 = settings.get_player2().to_string();
+    report.points = settings.get_points_to_win();
+    report.arena = settings.get_arena().to_string();
+
+    println!(
+        "Player 1 ({}) {} - {} Player 2 ({})",
+        report.player1, report.player1_wins, report.player2_wins, report.player2
+    );
+
+    if let Some(path) = &config.report {
+        let written = serde_json::to_string_pretty(report.as_ref())
+            .map_err(|error| error.to_string())
+            .and_then(|json| fs::write(path, json).map_err(|error| error.to_string()));
+
+        if let Err(error) = written {
+            error!("Could not write report to {}: {}", path.display(), error);
+            commands.send_event(AppExit::error());
+            return;
+        }
+    }
+
+    commands.send_event(AppExit::Success);
+}
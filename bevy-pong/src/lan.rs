@@ -0,0 +1,308 @@
+//! Finding streamed games on the local network.
+//!
+//! A host multicasts a [`LanSession`] as one JSON datagram every second, and once
+//! more with `open` unset when it shuts down. Browsers list the sessions heard from
+//! recently and forget the ones that went quiet. Every socket shares the discovery
+//! port, so several instances on one machine can host and browse side by side.
+//!
+//! Sides the host left to [`PlayerType::Remote`] players are announced as open slots
+//! until someone joins them through the [spectator stream](crate::spectator).
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::settings::{GameSettings, PlayerType};
+use crate::core::states::GameState;
+use crate::pong::ScoreField;
+use crate::spectator::SpectatorHost;
+
+pub const DISCOVERY_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 77, 77);
+pub const DISCOVERY_PORT: u16 = 47900;
+
+/// Marks announcements from this game among other traffic on the group.
+const GAME_ID: &str = "bevy-pong";
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a session stays listed after its last announcement.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(3);
+/// How often the browser thread looks up from the socket to see if it should stop.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// What a host tells the network about its game.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LanSession {
+    pub game: String,
+    /// Name the host goes by.
+    pub name: String,
+    pub rules: String,
+    pub players: [String; 2],
+    /// Whether a player on the network can still join the left and right side.
+    #[serde(default)]
+    pub open_slots: [bool; 2],
+    /// Whether a match is being played, rather than the host sitting in the menus.
+    pub in_match: bool,
+    pub spectators: usize,
+    /// Port the game is streamed on, at the address the announcement came from.
+    pub port: u16,
+    /// Unset in the host's last announcement before it leaves.
+    pub open: bool,
+}
+
+impl LanSession {
+    pub fn new(name: impl Into<String>, port: u16, settings: &GameSettings) -> Self {
+        Self {
+            game: GAME_ID.into(),
+            name: name.into(),
+            rules: settings.describe_rules(),
+            players: [
+                settings.get_player1().to_string(),
+                settings.get_player2().to_string(),
+            ],
+            open_slots: [settings.get_player1(), settings.get_player2()]
+                .map(|player| *player == PlayerType::Remote),
+            in_match: false,
+            spectators: 0,
+            port,
+            open: true,
+        }
+    }
+}
+
+/// A UDP socket on the discovery port that other sockets on this machine may share.
+fn discovery_socket(port: u16) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.set_multicast_loop_v4(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port).into())?;
+    Ok(socket.into())
+}
+
+/// Announces this game to browsers on the network.
+#[derive(Resource)]
+pub struct LanAdvertiser {
+    socket: UdpSocket,
+    target: SocketAddr,
+    session: LanSession,
+    next_announcement: Instant,
+}
+
+impl LanAdvertiser {
+    pub fn new(session: LanSession, discovery_port: u16) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_multicast_ttl_v4(1)?;
+
+        Ok(Self {
+            socket,
+            target: SocketAddrV4::new(DISCOVERY_GROUP, discovery_port).into(),
+            session,
+            next_announcement: Instant::now(),
+        })
+    }
+
+    pub fn session_mut(&mut self) -> &mut LanSession {
+        &mut self.session
+    }
+
+    /// Sends the session now.
+    pub fn announce(&self) -> io::Result<()> {
+        let message = serde_json::to_vec(&self.session)?;
+        self.socket.send_to(&message, self.target).map(|_| ())
+    }
+}
+
+impl Drop for LanAdvertiser {
+    fn drop(&mut self) {
+        self.session.open = false;
+        let _ = self.announce();
+    }
+}
+
+fn announce_session(
+    mut advertiser: ResMut<LanAdvertiser>,
+    settings: Res<GameSettings>,
+    state: Res<State<GameState>>,
+    host: Option<Res<SpectatorHost>>,
+) {
+    let now = Instant::now();
+    if now < advertiser.next_announcement {
+        return;
+    }
+    advertiser.next_announcement = now + ANNOUNCE_INTERVAL;
+
+    let session = advertiser.session_mut();
+    *session = LanSession {
+        in_match: matches!(state.get(), GameState::Playing | GameState::Endgame),
+        spectators: host.as_ref().map_or(0, |host| host.viewer_count()),
+        ..LanSession::new(session.name.clone(), session.port, &settings)
+    };
+    if let Some(host) = host {
+        for (open, side) in session
+            .open_slots
+            .iter_mut()
+            .zip([ScoreField::Left, ScoreField::Right])
+        {
+            *open &= !host.seat_taken(side);
+        }
+    }
+    if let Err(error) = advertiser.announce() {
+        warn!("Could not announce the game on the network: {}", error);
+    }
+}
+
+/// Advertises the game streamed on `port` under `name`.
+pub struct LanHostPlugin {
+    pub name: String,
+    pub port: u16,
+}
+
+impl Plugin for LanHostPlugin {
+    fn build(&self, app: &mut App) {
+        let session = LanSession::new(self.name.clone(), self.port, &GameSettings::default());
+        match LanAdvertiser::new(session, DISCOVERY_PORT) {
+            Ok(advertiser) => {
+                app.insert_resource(advertiser)
+                    .add_systems(Update, announce_session);
+            }
+            Err(error) => warn!("Could not advertise the game on the network: {}", error),
+        }
+    }
+}
+
+/// A session heard on the network, with the address to spectate it at.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DiscoveredSession {
+    pub address: SocketAddr,
+    pub session: LanSession,
+    last_seen: Instant,
+}
+
+/// Sessions currently announced on the network. Listening stops when it is dropped.
+#[derive(Resource)]
+pub struct LanBrowser {
+    announcements: Mutex<Receiver<(SocketAddr, LanSession)>>,
+    sessions: Vec<DiscoveredSession>,
+    stop: Arc<AtomicBool>,
+    port: u16,
+}
+
+impl LanBrowser {
+    pub fn open(discovery_port: u16) -> io::Result<Self> {
+        let socket = discovery_socket(discovery_port)?;
+        socket.join_multicast_v4(&DISCOVERY_GROUP, &Ipv4Addr::UNSPECIFIED)?;
+        socket.set_read_timeout(Some(STOP_CHECK_INTERVAL))?;
+        let port = socket.local_addr()?.port();
+
+        let (sender, announcements) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; 2048];
+            while !stopped.load(Ordering::Relaxed) {
+                let (len, from) = match socket.recv_from(&mut buffer) {
+                    Ok(received) => received,
+                    Err(error)
+                        if matches!(
+                            error.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                        ) =>
+                    {
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+                let Ok(session) = serde_json::from_slice::<LanSession>(&buffer[..len]) else {
+                    continue;
+                };
+                if session.game != GAME_ID {
+                    continue;
+                }
+                let address = SocketAddr::new(from.ip(), session.port);
+                if sender.send((address, session)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            announcements: Mutex::new(announcements),
+            sessions: Vec::new(),
+            stop,
+            port,
+        })
+    }
+
+    /// Port announcements are heard on, which the system picks when opened on port 0.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Sessions by name, then address.
+    pub fn sessions(&self) -> &[DiscoveredSession] {
+        &self.sessions
+    }
+
+    /// Takes in new announcements and drops sessions that closed or went quiet.
+    pub fn update(&mut self, now: Instant) {
+        if let Ok(announcements) = self.announcements.get_mut() {
+            for (address, session) in announcements.try_iter() {
+                self.sessions.retain(|known| known.address != address);
+                if session.open {
+                    self.sessions.push(DiscoveredSession {
+                        address,
+                        session,
+                        last_seen: now,
+                    });
+                }
+            }
+        }
+
+        self.sessions
+            .retain(|known| now.duration_since(known.last_seen) < SESSION_TIMEOUT);
+        self.sessions
+            .sort_by(|a, b| (&a.session.name, a.address).cmp(&(&b.session.name, b.address)));
+    }
+}
+
+impl Drop for LanBrowser {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn open_browser(mut commands: Commands) {
+    match LanBrowser::open(DISCOVERY_PORT) {
+        Ok(browser) => commands.insert_resource(browser),
+        Err(error) => warn!("Could not look for games on the network: {}", error),
+    }
+}
+
+fn close_browser(mut commands: Commands) {
+    commands.remove_resource::<LanBrowser>();
+}
+
+fn refresh_sessions(mut browser: ResMut<LanBrowser>) {
+    browser.update(Instant::now());
+}
+
+/// Lists the games on the network while the Join LAN Game screen is open.
+pub struct LanBrowserPlugin;
+
+impl Plugin for LanBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::JoinLan), open_browser)
+            .add_systems(OnExit(GameState::JoinLan), close_browser)
+            .add_systems(
+                Update,
+                refresh_sessions.run_if(resource_exists::<LanBrowser>),
+            );
+    }
+}
@@ -0,0 +1,544 @@
+//! Match results shared on a leaderboard service.
+//!
+//! Finished matches wait in `leaderboard_queue.json` in the data directory and are
+//! submitted in the background, so results from matches played offline go out once
+//! the service can be reached again. The service is any [`LeaderboardBackend`]:
+//! normally an [`HttpBackend`] talking to a server such as the one run by [`serve`],
+//! which keeps its results in a [`FileBackend`].
+//!
+//! The HTTP API has two endpoints:
+//!
+//! ```text
+//! POST /results                          a MatchResult as JSON
+//! GET  /standings?mode=<MODE>&limit=<N>  a JSON list of Standings, best first
+//! ```
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::settings::GameSettings;
+use crate::core::states::GameState;
+use crate::core::storage;
+use crate::pong::{match_result, MatchClock, Score, SurvivalRun};
+
+pub use crate::pong::{MatchResult, RESULT_MODES as MODES};
+
+const STORAGE_NAME: &str = "leaderboard";
+const QUEUE_STORAGE_NAME: &str = "leaderboard_queue";
+
+/// Results kept while the service is unreachable; the oldest go first.
+const MAX_QUEUED: usize = 200;
+/// How long submissions wait after the service couldn't take a result.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// Largest request body the server reads; a result is far smaller.
+const MAX_BODY_LENGTH: usize = 16 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const STANDINGS_SHOWN: usize = 10;
+
+/// Where results are sent, and the names human players appear under. Read from
+/// `leaderboard.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct LeaderboardSettings {
+    pub server: String,
+    pub player_names: [String; 2],
+}
+
+impl LeaderboardSettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+}
+
+impl Default for LeaderboardSettings {
+    fn default() -> Self {
+        Self {
+            server: "http://127.0.0.1:7880".into(),
+            player_names: ["Player 1".into(), "Player 2".into()],
+        }
+    }
+}
+
+/// A player's record in one mode.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Standing {
+    pub player: String,
+    pub matches: u32,
+    pub wins: u32,
+    pub best_score: u32,
+}
+
+/// Ranks every player of `mode` by wins, then best score. Survival runs only
+/// count the player on the left, since the right side is always the computer.
+pub fn standings(results: &[MatchResult], mode: &str, limit: usize) -> Vec<Standing> {
+    let mut players: HashMap<&str, Standing> = HashMap::new();
+    for result in results.iter().filter(|result| result.mode == mode) {
+        let sides = if mode == "survival" { 0..1 } else { 0..2 };
+        for side in sides {
+            let player = &result.players[side];
+            let standing = players.entry(player).or_insert_with(|| Standing {
+                player: player.clone(),
+                matches: 0,
+                wins: 0,
+                best_score: 0,
+            });
+            standing.matches += 1;
+            standing.wins += u32::from(result.winner == Some(side));
+            standing.best_score = standing.best_score.max(result.score[side]);
+        }
+    }
+
+    let mut ranked: Vec<Standing> = players.into_values().collect();
+    ranked.sort_by(|a, b| {
+        b.wins
+            .cmp(&a.wins)
+            .then(b.best_score.cmp(&a.best_score))
+            .then(a.matches.cmp(&b.matches))
+            .then_with(|| a.player.cmp(&b.player))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[derive(Debug)]
+pub enum LeaderboardError {
+    Unreachable(String),
+    /// The service refused the request itself; sending it again won't help.
+    Rejected(String),
+    /// The service failed to handle the request, but may manage later.
+    Failed(String),
+    Storage(io::Error),
+}
+
+impl fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaderboardError::Unreachable(reason) => {
+                write!(f, "leaderboard unreachable: {}", reason)
+            }
+            LeaderboardError::Rejected(reason) => write!(f, "leaderboard refused: {}", reason),
+            LeaderboardError::Failed(reason) => write!(f, "leaderboard failed: {}", reason),
+            LeaderboardError::Storage(error) => write!(f, "leaderboard storage: {}", error),
+        }
+    }
+}
+
+/// A place results are submitted to and standings read from.
+pub trait LeaderboardBackend: Send + Sync {
+    fn submit(&self, result: &MatchResult) -> Result<(), LeaderboardError>;
+
+    fn standings(&self, mode: &str, limit: usize) -> Result<Vec<Standing>, LeaderboardError>;
+}
+
+/// A leaderboard service reached over HTTP at `base_url`.
+pub struct HttpBackend {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpBackend {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').into(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        }
+    }
+}
+
+fn http_error(error: ureq::Error) -> LeaderboardError {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let reason = format!("{} {}", code, response.status_text());
+            if code >= 500 {
+                LeaderboardError::Failed(reason)
+            } else {
+                LeaderboardError::Rejected(reason)
+            }
+        }
+        ureq::Error::Transport(transport) => LeaderboardError::Unreachable(transport.to_string()),
+    }
+}
+
+impl LeaderboardBackend for HttpBackend {
+    fn submit(&self, result: &MatchResult) -> Result<(), LeaderboardError> {
+        self.agent
+            .post(&format!("{}/results", self.base_url))
+            .send_json(result)
+            .map(|_| ())
+            .map_err(http_error)
+    }
+
+    fn standings(&self, mode: &str, limit: usize) -> Result<Vec<Standing>, LeaderboardError> {
+        self.agent
+            .get(&format!("{}/standings", self.base_url))
+            .query("mode", mode)
+            .query("limit", &limit.to_string())
+            .call()
+            .map_err(http_error)?
+            .into_json()
+            .map_err(|error| LeaderboardError::Rejected(error.to_string()))
+    }
+}
+
+/// Every result ever submitted, kept in one JSON file.
+pub struct FileBackend {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn results(&self) -> Result<Vec<MatchResult>, LeaderboardError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|error| LeaderboardError::Storage(error.into())),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(error) => Err(LeaderboardError::Storage(error)),
+        }
+    }
+}
+
+impl LeaderboardBackend for FileBackend {
+    fn submit(&self, result: &MatchResult) -> Result<(), LeaderboardError> {
+        let _guard = self.lock.lock();
+        let mut results = self.results()?;
+        results.push(result.clone());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(LeaderboardError::Storage)?;
+        }
+        let contents = serde_json::to_string(&results)
+            .map_err(|error| LeaderboardError::Storage(error.into()))?;
+        fs::write(&self.path, contents).map_err(LeaderboardError::Storage)
+    }
+
+    fn standings(&self, mode: &str, limit: usize) -> Result<Vec<Standing>, LeaderboardError> {
+        let _guard = self.lock.lock();
+        Ok(standings(&self.results()?, mode, limit))
+    }
+}
+
+/// Answers leaderboard requests on `listener` from `backend`, one connection at a time.
+pub fn serve(listener: TcpListener, backend: &impl LeaderboardBackend) {
+    for stream in listener.incoming().flatten() {
+        if let Err(error) = handle_request(stream, backend) {
+            warn!("Leaderboard request failed: {}", error);
+        }
+    }
+}
+
+fn handle_request(stream: TcpStream, backend: &impl LeaderboardBackend) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return respond(stream, "413 Payload Too Large", "");
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let param = |key: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    };
+
+    let (status, response) = match (method.as_str(), path) {
+        ("POST", "/results") => match serde_json::from_slice::<MatchResult>(&body) {
+            Ok(result) => match backend.submit(&result) {
+                Ok(()) => ("201 Created", String::new()),
+                Err(error) => ("500 Internal Server Error", error.to_string()),
+            },
+            Err(error) => ("400 Bad Request", error.to_string()),
+        },
+        ("GET", "/standings") => {
+            let mode = param("mode").unwrap_or(MODES[0]);
+            let limit = param("limit")
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(STANDINGS_SHOWN);
+            match backend.standings(mode, limit) {
+                Ok(standings) => ("200 OK", serde_json::to_string(&standings)?),
+                Err(error) => ("500 Internal Server Error", error.to_string()),
+            }
+        }
+        _ => ("404 Not Found", String::new()),
+    };
+
+    respond(stream, status, &response)
+}
+
+fn respond(mut stream: TcpStream, status: &str, response: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    )?;
+    stream.flush()
+}
+
+/// How many queued results a submission is done with, why those the backend refused
+/// were dropped, and why it stopped early.
+struct Submitted {
+    count: usize,
+    refused: Vec<String>,
+    error: Option<String>,
+}
+
+/// Results waiting to be submitted, and the background submission in progress.
+#[derive(Resource)]
+pub struct Leaderboard {
+    backend: Arc<dyn LeaderboardBackend>,
+    queue: Vec<MatchResult>,
+    submission: Option<Mutex<Receiver<Submitted>>>,
+    next_attempt: Instant,
+}
+
+impl Leaderboard {
+    pub fn new(backend: impl LeaderboardBackend + 'static, queue: Vec<MatchResult>) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            queue,
+            submission: None,
+            next_attempt: Instant::now(),
+        }
+    }
+
+    pub fn backend(&self) -> Arc<dyn LeaderboardBackend> {
+        self.backend.clone()
+    }
+
+    /// Results not yet accepted by the backend, oldest first.
+    pub fn pending(&self) -> &[MatchResult] {
+        &self.queue
+    }
+
+    pub fn enqueue(&mut self, result: MatchResult) {
+        self.queue.push(result);
+        self.evict_oldest();
+        self.next_attempt = Instant::now();
+    }
+
+    /// Drops the oldest results past [`MAX_QUEUED`]. A submission in progress counts
+    /// the results it got through from the front of the queue, so nothing is dropped
+    /// until it finishes.
+    fn evict_oldest(&mut self) {
+        if self.submission.is_none() {
+            let excess = self.queue.len().saturating_sub(MAX_QUEUED);
+            self.queue.drain(..excess);
+        }
+    }
+
+    /// Collects a finished submission and starts the next one when results are
+    /// waiting. Returns whether the queue changed.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        if let Some(submission) = self.submission.as_mut() {
+            let outcome = match submission.get_mut() {
+                Ok(receiver) => receiver.try_recv(),
+                Err(_) => Err(TryRecvError::Disconnected),
+            };
+            match outcome {
+                Ok(Submitted {
+                    count,
+                    refused,
+                    error,
+                }) => {
+                    for reason in refused {
+                        warn!("{}; dropping the result", reason);
+                    }
+                    self.queue.drain(..count.min(self.queue.len()));
+                    changed = count > 0;
+                    if let Some(error) = error {
+                        warn!("{}; retrying in {}s", error, RETRY_INTERVAL.as_secs());
+                        self.next_attempt = now + RETRY_INTERVAL;
+                    }
+                    self.submission = None;
+                }
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => {
+                    self.next_attempt = now + RETRY_INTERVAL;
+                    self.submission = None;
+                }
+            }
+            let len = self.queue.len();
+            self.evict_oldest();
+            changed |= self.queue.len() != len;
+        }
+
+        if self.queue.is_empty() || now < self.next_attempt {
+            return changed;
+        }
+
+        let backend = self.backend.clone();
+        let results = self.queue.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut submitted = Submitted {
+                count: 0,
+                refused: Vec::new(),
+                error: None,
+            };
+            for result in &results {
+                match backend.submit(result) {
+                    Ok(()) => submitted.count += 1,
+                    Err(error @ LeaderboardError::Rejected(_)) => {
+                        submitted.count += 1;
+                        submitted.refused.push(error.to_string());
+                    }
+                    Err(error) => {
+                        submitted.error = Some(error.to_string());
+                        break;
+                    }
+                }
+            }
+            let _ = sender.send(submitted);
+        });
+        self.submission = Some(Mutex::new(receiver));
+        changed
+    }
+
+    fn save(&self) {
+        if let Err(error) = storage::save(QUEUE_STORAGE_NAME, &self.queue) {
+            warn!("Could not save the leaderboard queue: {}", error);
+        }
+    }
+}
+
+fn record_result(
+    mut leaderboard: ResMut<Leaderboard>,
+    settings: Res<GameSettings>,
+    names: Res<LeaderboardSettings>,
+    score: Res<Score>,
+    clock: Res<MatchClock>,
+    run: Res<SurvivalRun>,
+) {
+    let human_names = Some(&names.player_names);
+    if let Some(result) = match_result(&settings, &score, &clock, &run, human_names) {
+        leaderboard.enqueue(result);
+        leaderboard.save();
+    }
+}
+
+fn submit_results(mut leaderboard: ResMut<Leaderboard>) {
+    if leaderboard.poll(Instant::now()) {
+        leaderboard.save();
+    }
+}
+
+/// A mode's standings, or why they couldn't be fetched.
+type ModeStandings = (&'static str, Result<Vec<Standing>, String>);
+
+/// Standings for the Leaderboards screen, filled in as they arrive.
+#[derive(Resource)]
+pub struct LeaderboardStandings {
+    receiver: Mutex<Receiver<ModeStandings>>,
+    modes: Vec<ModeStandings>,
+}
+
+impl LeaderboardStandings {
+    fn fetch(backend: Arc<dyn LeaderboardBackend>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for mode in MODES {
+                let standings = backend
+                    .standings(mode, STANDINGS_SHOWN)
+                    .map_err(|error| error.to_string());
+                if sender.send((mode, standings)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver: Mutex::new(receiver),
+            modes: Vec::new(),
+        }
+    }
+
+    /// Standings by mode, `None` while still loading.
+    pub fn get(&self, mode: &str) -> Option<&Result<Vec<Standing>, String>> {
+        self.modes
+            .iter()
+            .find(|(name, _)| *name == mode)
+            .map(|(_, standings)| standings)
+    }
+}
+
+fn fetch_standings(mut commands: Commands, leaderboard: Res<Leaderboard>) {
+    commands.insert_resource(LeaderboardStandings::fetch(leaderboard.backend()));
+}
+
+fn receive_standings(mut standings: ResMut<LeaderboardStandings>) {
+    let standings = &mut *standings;
+    if let Ok(receiver) = standings.receiver.get_mut() {
+        standings.modes.extend(receiver.try_iter());
+    }
+}
+
+fn drop_standings(mut commands: Commands) {
+    commands.remove_resource::<LeaderboardStandings>();
+}
+
+/// Submits finished matches to the leaderboard server in `leaderboard.json`.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = LeaderboardSettings::load();
+        let queue = storage::load(QUEUE_STORAGE_NAME);
+
+        app.insert_resource(Leaderboard::new(HttpBackend::new(&settings.server), queue))
+            .insert_resource(settings)
+            .add_systems(OnEnter(GameState::Endgame), record_result)
+            .add_systems(OnEnter(GameState::Leaderboards), fetch_standings)
+            .add_systems(OnExit(GameState::Leaderboards), drop_standings)
+            .add_systems(
+                Update,
+                (
+                    submit_results,
+                    receive_standings.run_if(resource_exists::<LeaderboardStandings>),
+                ),
+            );
+    }
+}
@@ -0,0 +1,24 @@
+pub mod windows {
+    pub mod camera;
+    pub mod window;
+}
+
+pub mod accessibility;
+pub mod achievements;
+pub mod audio;
+pub mod bot;
+pub mod cli;
+pub mod core;
+#[cfg(debug_assertions)]
+pub mod debug;
+pub mod effects;
+pub mod env;
+pub mod events;
+pub mod headless;
+pub mod lan;
+pub mod leaderboard;
+pub mod pong;
+pub mod spectator;
+pub mod theme;
+pub mod tuner;
+pub mod ui;
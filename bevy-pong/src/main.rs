@@ -1,30 +1,161 @@
 use bevy::prelude::*;
 
-use core::GamePlugin;
-use pong::PongPlugin;
-use ui::MenuSystemsPlugin;
+use bevy_pong::accessibility::AccessibilityPlugin;
+use bevy_pong::achievements::AchievementsPlugin;
+use bevy_pong::audio::GameAudioPlugin;
+use bevy_pong::bot::BotPlugin;
+use bevy_pong::cli::{self, CliError};
+use bevy_pong::core::controls::GameControlsPlugin;
+use bevy_pong::core::high_scores::HighScores;
+use bevy_pong::core::settings::{AiPresets, PracticeSettings, SurvivalSettings};
+use bevy_pong::core::storage;
+use bevy_pong::core::GamePlugin;
+use bevy_pong::effects::EffectsPlugin;
+use bevy_pong::events::{EventLog, GameplayEventsPlugin};
+use bevy_pong::headless::HeadlessPlugin;
+use bevy_pong::lan::{LanBrowserPlugin, LanHostPlugin};
+use bevy_pong::leaderboard::{self, FileBackend, LeaderboardPlugin};
+use bevy_pong::pong::PongPlugin;
+use bevy_pong::spectator::{SpectatorFeed, SpectatorHostPlugin, SpectatorViewerPlugin};
+use bevy_pong::theme::ThemePlugin;
+use bevy_pong::tuner;
+use bevy_pong::ui::MenuSystemsPlugin;
 
 //
-use windows::camera::GameCameraPlugin;
-use windows::window::GameWindowPlugin;
+use bevy_pong::windows::camera::GameCameraPlugin;
+use bevy_pong::windows::window::GameWindowPlugin;
 
-mod windows {
-    pub mod camera;
-    pub mod window;
-}
+fn main() {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(CliError::Help) => {
+            println!("{}", CliError::Help);
+            return;
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
 
-mod core;
-mod pong;
-mod ui;
+    if let Some(config) = options.tune {
+        let settings = options.settings.unwrap_or_default();
+        let presets = tuner::tune(settings, AiPresets::load(), &config, |progress| {
+            println!("{}", progress)
+        });
+        if let Err(error) = presets.save() {
+            eprintln!("error: could not save AI presets: {}", error);
+            std::process::exit(1);
+        }
+        println!("Saved AI presets: {:?}", presets);
+        return;
+    }
 
-fn main() {
-    App::new()
-        .add_plugins((
+    if let Some(port) = options.leaderboard_server {
+        let path = storage::data_dir().join("leaderboard_server.json");
+        let listener = match std::net::TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => listener,
+            Err(error) => {
+                eprintln!("error: could not listen on port {}: {}", port, error);
+                std::process::exit(1);
+            }
+        };
+        println!(
+            "Serving the leaderboard in {} on port {}",
+            path.display(),
+            port
+        );
+        leaderboard::serve(listener, &FileBackend::new(path));
+        return;
+    }
+
+    let mut app = App::new();
+    if let Some(address) = options.spectate {
+        let mut feed = SpectatorFeed::new(address, options.spectate_delay);
+        if let Some(side) = options.play {
+            feed = feed.joining(side);
+        }
+        app.add_plugins((
+            GameWindowPlugin,
+            GameCameraPlugin,
+            GameControlsPlugin,
+            AccessibilityPlugin,
+            ThemePlugin,
+            SpectatorViewerPlugin,
+        ))
+        .insert_resource(feed)
+        .run();
+        return;
+    }
+
+    if options.headless {
+        app.add_plugins((
+            HeadlessPlugin::new(options.matches, options.report),
+            GamePlugin,
+            PongPlugin,
+            BotPlugin,
+            GameplayEventsPlugin,
+        ));
+    } else {
+        app.add_plugins((
             GameWindowPlugin,
             GameCameraPlugin,
             GamePlugin,
             MenuSystemsPlugin,
             PongPlugin,
+            BotPlugin,
+            GameplayEventsPlugin,
+            GameAudioPlugin,
+            AccessibilityPlugin,
+            AchievementsPlugin,
+            ThemePlugin,
+            EffectsPlugin,
+            SpectatorViewerPlugin,
+            LanBrowserPlugin,
+            LeaderboardPlugin,
         ))
-        .run();
+        // The player's saved settings replace the defaults the plugins start with.
+        .insert_resource(PracticeSettings::load())
+        .insert_resource(SurvivalSettings::load())
+        .insert_resource(HighScores::load());
+
+        #[cfg(debug_assertions)]
+        app.add_plugins(bevy_pong::debug::DebugPlugin);
+    }
+
+    // Presets calibrated with --tune replace the built-in ones.
+    app.insert_resource(AiPresets::load());
+
+    if let Some(port) = options.stream {
+        app.add_plugins((
+            SpectatorHostPlugin { port },
+            LanHostPlugin {
+                name: options.name,
+                port,
+            },
+        ));
+    }
+
+    if let Some(path) = options.events {
+        match EventLog::create(&path) {
+            Ok(log) => {
+                app.insert_resource(log);
+            }
+            Err(error) => {
+                eprintln!("error: could not create {}: {}", path.display(), error);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(bots) = options.bots {
+        app.insert_resource(bots);
+    }
+
+    if let Some(settings) = options.settings {
+        app.insert_resource(settings)
+            .add_systems(Startup, cli::start_match);
+    }
+
+    app.run();
 }
@@ -1,12 +1,16 @@
 mod components;
-mod constants;
+pub mod constants;
 mod observers;
 mod resources;
+mod results;
 mod systems;
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::core::high_scores::HighScores;
+use crate::core::rng::GameRng;
+use crate::core::settings::{AiPresets, PracticeSettings, SurvivalSettings};
 use crate::core::states::{GameState, PausedState, PlayingSet};
 use observers::*;
 use systems::*;
@@ -16,6 +20,23 @@ pub struct PongPlugin;
 impl Plugin for PongPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Score>()
+            .init_resource::<GameRng>()
+            .init_resource::<MatchClock>()
+            .init_resource::<WinConditions>()
+            .init_resource::<PointerTargets>()
+            .init_resource::<ExternalInput>()
+            .init_resource::<PracticeStats>()
+            .init_resource::<resources::Launcher>()
+            .init_resource::<PracticeSettings>()
+            .init_resource::<AiPresets>()
+            .init_resource::<SurvivalRun>()
+            .init_resource::<SurvivalSettings>()
+            .init_resource::<HighScores>()
+            .insert_resource(Time::<Fixed>::from_hz(constants::TICK_RATE))
+            .insert_resource(TimestepMode::Fixed {
+                dt: 1. / constants::TICK_RATE as f32,
+                substeps: 1,
+            })
             .configure_sets(
                 FixedUpdate,
                 (PhysicsSet::StepSimulation
@@ -23,24 +44,88 @@ impl Plugin for PongPlugin {
                     .run_if(in_state(PausedState::Playing)),),
             )
             .add_event::<OnPointScored>()
-            .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_event::<OnPaddleHit>()
+            .add_event::<OnWallBounce>()
+            .add_event::<OnBallStalled>()
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule())
             .add_observer(score_point)
             .add_observer(reset_ball)
+            .add_observer(reserve_ball)
+            .add_observer(restart_match)
             .add_observer(end_game)
+            .add_observer(reroll_aim)
+            .add_observer(practice_miss)
+            .add_observer(practice_return)
+            .add_observer(escalate_survival)
+            .add_observer(survival_return)
             .add_systems(OnEnter(GameState::Playing), setup_game)
             .add_systems(OnExit(GameState::Playing), cleanup_game)
-            .add_systems(FixedUpdate, move_players.in_set(PlayingSet))
             .add_systems(
-                Update,
+                OnEnter(GameState::Endgame),
+                record_high_score.run_if(in_survival),
+            )
+            .add_systems(
+                FixedUpdate,
+                (move_players, count_down_serves, tick_match_clock)
+                    .in_set(PlayingSet)
+                    .before(PhysicsSet::SyncBackend),
+            )
+            .add_systems(
+                FixedUpdate,
+                // Chained so that every run with the same seed applies the rules in the same order.
                 (
-                    speed_up_ball,
                     ball_paddle_collision,
+                    ball_wall_collision,
+                    enforce_ball_limits,
                     detect_point,
-                    update_score_display,
+                    detect_ball_stall,
+                    check_win_conditions,
+                )
+                    .chain()
+                    .in_set(PlayingSet)
+                    .after(PhysicsSet::Writeback),
+            )
+            .add_systems(
+                FixedUpdate,
+                (launch_balls, apply_spin)
+                    .chain()
+                    .in_set(PlayingSet)
+                    .run_if(in_practice)
+                    .before(PhysicsSet::SyncBackend),
+            )
+            .add_systems(
+                FixedUpdate,
+                absorb_returns
+                    .in_set(PlayingSet)
+                    .run_if(in_practice)
+                    .after(detect_ball_stall),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_score_display.run_if(in_match),
+                    update_practice_display.run_if(in_practice),
+                    update_survival_display.run_if(in_survival),
+                    update_clock_display,
+                    track_pointers,
                 )
                     .in_set(PlayingSet),
             );
     }
 }
 
-pub use resources::Score;
+pub use components::{
+    AiTarget, Ball, ClockText, Pong, ScoreField, ScoreText, ServeCountdown, Wall,
+};
+pub use observers::{
+    AfterPointScored, OnBallStalled, OnPaddleHit, OnPointScored, OnWallBounce, RestartMatch,
+};
+pub use resources::{
+    ExternalInput, FirstToPoints, MatchClock, OutOfLives, PointerTargets, PracticeStats, Score,
+    SurvivalRun, TimeLimit, WinCondition, WinConditions,
+};
+pub use results::{match_result, MatchResult, RESULT_MODES};
+pub use systems::{
+    ball::{constrain_velocity, serve_velocity},
+    setup::{serve_ball, spawn_ball},
+};
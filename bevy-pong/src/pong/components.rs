@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Component)]
 pub struct Pong;
@@ -6,7 +8,61 @@ pub struct Pong;
 #[derive(Component)]
 pub struct Ball;
 
+/// Fixed ticks since the ball last touched a paddle.
+#[derive(Component, Default)]
+pub struct Rally {
+    pub ticks_since_hit: u32,
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+/// Practice-mode wall standing in for the opponent's goal.
+#[derive(Component)]
+pub struct ReboundWall;
+
+/// Holds a ball still until its serve countdown runs out.
 #[derive(Component)]
+pub struct ServeCountdown {
+    pub ticks: u32,
+    pub velocity: Vec2,
+}
+
+#[derive(Component)]
+pub struct ScoreText;
+
+#[derive(Component)]
+pub struct ClockText;
+
+/// Vertical acceleration curving a ball until a paddle returns it.
+#[derive(Component)]
+pub struct Spin(pub f32);
+
+/// Point a computer-controlled paddle is currently moving towards.
+#[derive(Component, Default)]
+pub struct AiTarget(pub Vec2);
+
+/// Vertical error a computer-controlled paddle adds to its aim, re-rolled on every hit.
+#[derive(Component, Default)]
+pub struct AimOffset(pub f32);
+
+/// Ball heights a computer-controlled paddle has seen, oldest first, so it can
+/// react with a delay.
+#[derive(Component, Default)]
+pub struct AiMemory(pub VecDeque<f32>);
+
+/// Paddle velocity carried between ticks by the momentum movement model.
+#[derive(Component, Default)]
+pub struct PaddleMotion {
+    pub velocity: f32,
+    pub dash_direction: f32,
+    pub dash_ticks: u32,
+    pub cooldown: u32,
+    /// Direction a key press set the paddle moving in, with toggled input.
+    pub toggled: f32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum ScoreField {
     Left,
     Right,
@@ -1,8 +1,16 @@
 pub const WALL_THICKNESS: f32 = 10.0;
 pub const TOP_BUFFER: f32 = 100.0;
+pub const TICK_RATE: f64 = 64.0;
 
 pub mod game {
     pub const MAX_SCORE: u32 = 5;
+    /// Ticks a ball waits at the centre before each serve in timed matches.
+    pub const SERVE_COUNTDOWN_TICKS: u32 = 64;
+}
+
+pub mod survival {
+    /// Opponent level gained for every point the player wins.
+    pub const LEVEL_STEP: f32 = 0.25;
 }
 
 pub mod paddle {
@@ -10,11 +18,23 @@ pub mod paddle {
     pub const HEIGHT: f32 = 100.0;
     pub const BUFFER: f32 = 40.0;
     pub const SPEED: f32 = 10.;
+    /// Speed gained per tick while accelerating with paddle momentum.
+    pub const ACCELERATION: f32 = 1.5;
+    pub const DECELERATION: f32 = 2.5;
+    pub const DASH_SPEED: f32 = 30.;
+    pub const DASH_TICKS: u32 = 6;
+    pub const DASH_COOLDOWN_TICKS: u32 = 64;
 }
 
 pub mod ball {
     pub const RADIUS: f32 = 8.0;
-    pub const INITIAL_VELOCITY: (f32, f32) = (200.0, 100.0);
-    pub const SPEED_INCREASE: f32 = 2.;
+    pub const SERVE_SPEED: f32 = 225.0;
+    pub const MAX_SERVE_ANGLE: f32 = 0.5;
+    pub const SPEED_INCREASE: f32 = 1.05;
     pub const MAX_BALL_SPEED: f32 = 1000.;
+    pub const MIN_HORIZONTAL_SPEED: f32 = 120.;
+    /// Steepest angle from the horizontal the ball may travel at.
+    pub const MAX_BOUNCE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+    /// Ticks without touching a paddle before the ball is served again.
+    pub const STALL_TICKS: u32 = 12 * 64;
 }
@@ -1,8 +1,11 @@
-use super::components::{Ball, Pong, ScoreField};
-use super::resources::Score;
-use crate::core::settings::PlayerType;
+use super::components::{AimOffset, Ball, Pong, ScoreField};
+use super::constants;
+use super::resources::{Launcher, MatchClock, PracticeStats, Score, SurvivalRun, WinConditions};
+use crate::core::rng::{GameRng, RngStream};
+use crate::core::settings::{AiPresets, Difficulty, GameMode, GameSettings, PlayerType};
 use crate::core::states::GameState;
 use bevy::prelude::*;
+use rand::Rng;
 
 #[derive(Event, Debug)]
 pub struct OnPointScored(pub Entity);
@@ -10,40 +13,191 @@ pub struct OnPointScored(pub Entity);
 #[derive(Event)]
 pub struct AfterPointScored;
 
+/// The ball stopped making progress towards either paddle and must be served again.
+#[derive(Event)]
+pub struct OnBallStalled(pub Entity);
+
+/// Starts the running match over from 0 - 0 with the same settings.
+#[derive(Event)]
+pub struct RestartMatch;
+
+#[derive(Event)]
+pub struct OnPaddleHit {
+    /// Side of the paddle that returned the ball.
+    pub side: ScoreField,
+    pub speed: f32,
+    /// Where on the paddle the ball landed, from -1 at the bottom to 1 at the top.
+    pub offset: f32,
+    /// Where the ball was when it touched the paddle.
+    pub position: Vec2,
+}
+
+#[derive(Event)]
+pub struct OnWallBounce {
+    pub speed: f32,
+    pub position: Vec2,
+}
+
 pub fn score_point(
     trigger: Trigger<OnPointScored>,
     mut commands: Commands,
     mut score: ResMut<Score>,
+    settings: Res<GameSettings>,
     walls: Query<&ScoreField, Without<PlayerType>>,
 ) {
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+
     if let Ok(wall) = walls.get(trigger.0) {
         score.add_point(wall);
         commands.trigger(AfterPointScored);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn reset_ball(
     _: Trigger<OnPointScored>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+    settings: Res<GameSettings>,
     ball_entity: Query<Entity, With<Ball>>,
     pong_entity: Query<Entity, With<Pong>>,
 ) {
-    commands.entity(ball_entity.single()).despawn();
+    if settings.get_mode() == GameMode::Practice {
+        return;
+    }
+    let timed = settings.mode_time_limit().is_some();
+
+    for ball in ball_entity.iter() {
+        commands.entity(ball).despawn();
+    }
 
     let pong = pong_entity.single();
     commands.entity(pong).with_children(|parent| {
-        super::setup::spawn_ball(parent, &mut meshes, &mut materials);
+        let serve = super::ball::serve_velocity(&mut rng);
+        super::setup::serve_ball(parent, &mut meshes, &mut materials, serve, timed);
     });
 }
 
+/// Replaces a stalled ball: in practice the launcher serves the next one right away,
+/// otherwise it is served again like after a point.
+#[allow(clippy::too_many_arguments)]
+pub fn reserve_ball(
+    trigger: Trigger<OnBallStalled>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+    mut launcher: ResMut<Launcher>,
+    settings: Res<GameSettings>,
+    pong_entity: Query<Entity, With<Pong>>,
+) {
+    commands.entity(trigger.0).despawn();
+    if settings.get_mode() == GameMode::Practice {
+        launcher.ticks_until_launch = 0;
+        return;
+    }
+    let timed = settings.mode_time_limit().is_some();
+
+    let pong = pong_entity.single();
+    commands.entity(pong).with_children(|parent| {
+        let serve = super::ball::serve_velocity(&mut rng);
+        super::setup::serve_ball(parent, &mut meshes, &mut materials, serve, timed);
+    });
+}
+
+pub fn restart_match(_: Trigger<RestartMatch>, mut commands: Commands) {
+    commands.run_system_cached(super::cleanup_game);
+    commands.run_system_cached(super::setup_game);
+}
+
+/// Counts a practice miss and clears the balls that got past the paddle.
+pub fn practice_miss(
+    _: Trigger<OnPointScored>,
+    mut commands: Commands,
+    mut stats: ResMut<PracticeStats>,
+    settings: Res<GameSettings>,
+    balls: Query<(Entity, &Transform), With<Ball>>,
+) {
+    if settings.get_mode() != GameMode::Practice {
+        return;
+    }
+
+    stats.add_miss();
+    let goal_line = settings.get_arena().size().x / -2. + constants::paddle::BUFFER;
+    for (ball, transform) in balls.iter() {
+        if transform.translation.x < goal_line {
+            commands.entity(ball).despawn();
+        }
+    }
+}
+
+pub fn practice_return(
+    _: Trigger<OnPaddleHit>,
+    mut stats: ResMut<PracticeStats>,
+    settings: Res<GameSettings>,
+) {
+    if settings.get_mode() == GameMode::Practice {
+        stats.add_return();
+    }
+}
+
+/// Raises the opponent's level after every point the player wins in survival.
+pub fn escalate_survival(
+    trigger: Trigger<OnPointScored>,
+    mut run: ResMut<SurvivalRun>,
+    settings: Res<GameSettings>,
+    walls: Query<&ScoreField, Without<PlayerType>>,
+    mut paddles: Query<(&mut PlayerType, &ScoreField)>,
+) {
+    let player_scored = matches!(walls.get(trigger.0), Ok(ScoreField::Right));
+    if settings.get_mode() != GameMode::Survival || !player_scored {
+        return;
+    }
+
+    run.level += constants::survival::LEVEL_STEP;
+    for (mut player_type, field) in paddles.iter_mut() {
+        if *field == ScoreField::Right {
+            *player_type = PlayerType::Computer(Difficulty::Level(run.level));
+        }
+    }
+}
+
+pub fn survival_return(
+    trigger: Trigger<OnPaddleHit>,
+    mut run: ResMut<SurvivalRun>,
+    settings: Res<GameSettings>,
+) {
+    if settings.get_mode() == GameMode::Survival && trigger.side == ScoreField::Left {
+        run.add_return();
+    }
+}
+
 pub fn end_game(
     _: Trigger<AfterPointScored>,
     score: Res<Score>,
+    clock: Res<MatchClock>,
+    conditions: Res<WinConditions>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if score.is_game_end() {
+    if conditions.is_met(&score, &clock) {
         next_state.set(GameState::Endgame);
     }
 }
+
+pub fn reroll_aim(
+    _: Trigger<OnPaddleHit>,
+    mut rng: ResMut<GameRng>,
+    presets: Res<AiPresets>,
+    mut paddles: Query<(&mut AimOffset, &PlayerType)>,
+) {
+    for (mut offset, player_type) in paddles.iter_mut() {
+        if let PlayerType::Computer(difficulty) = player_type {
+            let error = presets.profile(difficulty).prediction_noise;
+            offset.0 = rng.stream(RngStream::Ai).gen_range(-error..=error);
+        }
+    }
+}
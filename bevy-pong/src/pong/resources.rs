@@ -1,17 +1,28 @@
 use super::components::ScoreField;
-use crate::pong::constants::game::MAX_SCORE;
+use crate::core::high_scores::HighScore;
+use crate::core::settings::{GameMode, GameSettings, SurvivalSettings};
+use crate::pong::constants::{game::MAX_SCORE, TICK_RATE};
 use bevy::prelude::*;
 
 #[derive(Resource)]
 pub struct Score {
     player1: u32,
     player2: u32,
+    max_score: u32,
+    forfeited: Option<ScoreField>,
 }
 
 impl Score {
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, max_score: u32) {
         self.player1 = 0;
         self.player2 = 0;
+        self.max_score = max_score;
+        self.forfeited = None;
+    }
+
+    pub fn set(&mut self, player1: u32, player2: u32) {
+        self.player1 = player1;
+        self.player2 = player2;
     }
 
     pub fn add_point(&mut self, field: &ScoreField) {
@@ -21,16 +32,43 @@ impl Score {
         }
     }
 
+    /// Ends the match as a loss for the paddle defending `side`.
+    pub fn forfeit(&mut self, side: ScoreField) {
+        self.forfeited = Some(side);
+    }
+
+    pub fn forfeited(&self) -> Option<ScoreField> {
+        self.forfeited
+    }
+
+    pub fn player1(&self) -> u32 {
+        self.player1
+    }
+
+    pub fn player2(&self) -> u32 {
+        self.player2
+    }
+
     pub fn display_text(&self) -> String {
         format!("{} - {}", self.player1, self.player2)
     }
 
+    /// Whether either player is one point away from winning.
+    pub fn is_match_point(&self) -> bool {
+        !self.is_game_end() && self.player1.max(self.player2) + 1 >= self.max_score
+    }
+
     pub fn is_game_end(&self) -> bool {
-        self.player1 >= MAX_SCORE || self.player2 >= MAX_SCORE
+        self.forfeited.is_some() || self.player1 >= self.max_score || self.player2 >= self.max_score
     }
 
     pub fn get_winner(&self) -> String {
-        if self.player1 >= MAX_SCORE {
+        if let Some(side) = self.forfeited {
+            match side {
+                ScoreField::Left => "Player 2".into(),
+                ScoreField::Right => "Player 1".into(),
+            }
+        } else if self.player1 > self.player2 {
             "Player 1".into()
         } else {
             "Player 2".into()
@@ -43,6 +81,245 @@ impl Default for Score {
         Self {
             player1: 0,
             player2: 0,
+            max_score: MAX_SCORE,
+            forfeited: None,
+        }
+    }
+}
+
+/// Fixed ticks of play in the current match, excluding pauses and serve countdowns.
+#[derive(Resource, Default)]
+pub struct MatchClock {
+    ticks: u64,
+    limit: Option<u64>,
+}
+
+impl MatchClock {
+    pub fn reset(&mut self, limit_seconds: Option<u32>) {
+        self.ticks = 0;
+        self.limit = limit_seconds.map(|seconds| (seconds as f64 * TICK_RATE) as u64);
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.ticks as f64 / TICK_RATE
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.limit.is_some()
+    }
+
+    pub fn is_time_up(&self) -> bool {
+        self.limit.is_some_and(|limit| self.ticks >= limit)
+    }
+
+    pub fn display_text(&self) -> String {
+        let Some(limit) = self.limit else {
+            return String::new();
+        };
+        if self.is_time_up() {
+            return "Golden Point".into();
+        }
+
+        let seconds = ((limit - self.ticks) as f64 / TICK_RATE).ceil() as u64;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// A rule that can end the match, checked after every point and tick.
+pub trait WinCondition: Send + Sync + 'static {
+    fn is_met(&self, score: &Score, clock: &MatchClock) -> bool;
+}
+
+/// First player to the score's points to win.
+pub struct FirstToPoints;
+
+impl WinCondition for FirstToPoints {
+    fn is_met(&self, score: &Score, _: &MatchClock) -> bool {
+        score.is_game_end()
+    }
+}
+
+/// Survival ends once the player has lost all their lives.
+pub struct OutOfLives(pub u32);
+
+impl WinCondition for OutOfLives {
+    fn is_met(&self, score: &Score, _: &MatchClock) -> bool {
+        score.forfeited().is_some() || score.player2() >= self.0
+    }
+}
+
+/// Ends when time is up and someone leads; a tie continues as golden point.
+pub struct TimeLimit;
+
+impl WinCondition for TimeLimit {
+    fn is_met(&self, score: &Score, clock: &MatchClock) -> bool {
+        score.forfeited().is_some() || (clock.is_time_up() && score.player1() != score.player2())
+    }
+}
+
+/// Conditions of the running match; the match ends as soon as any is met.
+#[derive(Resource)]
+pub struct WinConditions(Vec<Box<dyn WinCondition>>);
+
+impl WinConditions {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn with(mut self, condition: impl WinCondition) -> Self {
+        self.0.push(Box::new(condition));
+        self
+    }
+
+    pub fn for_settings(settings: &GameSettings, survival: &SurvivalSettings) -> Self {
+        match (settings.get_mode(), settings.mode_time_limit()) {
+            (GameMode::Survival, _) => Self::new().with(OutOfLives(survival.lives)),
+            (_, Some(_)) => Self::new().with(TimeLimit),
+            (_, None) => Self::new().with(FirstToPoints),
+        }
+    }
+
+    pub fn is_met(&self, score: &Score, clock: &MatchClock) -> bool {
+        self.0
+            .iter()
+            .any(|condition| condition.is_met(score, clock))
+    }
+}
+
+impl Default for WinConditions {
+    fn default() -> Self {
+        Self::new().with(FirstToPoints)
+    }
+}
+
+/// World-space heights that pointer-controlled paddles move towards.
+#[derive(Resource, Default)]
+pub struct PointerTargets {
+    pub left: Option<f32>,
+    pub right: Option<f32>,
+}
+
+/// Returns and misses in the running practice session.
+#[derive(Resource, Default)]
+pub struct PracticeStats {
+    returns: u32,
+    misses: u32,
+}
+
+impl PracticeStats {
+    pub fn reset(&mut self) {
+        self.returns = 0;
+        self.misses = 0;
+    }
+
+    pub fn add_return(&mut self) {
+        self.returns += 1;
+    }
+
+    pub fn add_miss(&mut self) {
+        self.misses += 1;
+    }
+
+    pub fn returns(&self) -> u32 {
+        self.returns
+    }
+
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    pub fn return_percentage(&self) -> f32 {
+        match self.returns + self.misses {
+            0 => 0.,
+            total => self.returns as f32 / total as f32 * 100.,
+        }
+    }
+
+    pub fn display_text(&self) -> String {
+        format!(
+            "{} / {} ({:.0}%)",
+            self.returns,
+            self.returns + self.misses,
+            self.return_percentage()
+        )
+    }
+}
+
+/// Fixed ticks until the practice launcher serves again.
+#[derive(Resource, Default)]
+pub struct Launcher {
+    pub ticks_until_launch: u32,
+}
+
+/// Progress of the running survival game.
+#[derive(Resource, Default)]
+pub struct SurvivalRun {
+    pub level: f32,
+    returns: u32,
+    /// Place in the high score table, once the run has ended and made it.
+    pub rank: Option<usize>,
+}
+
+impl SurvivalRun {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn add_return(&mut self) {
+        self.returns += 1;
+    }
+
+    pub fn returns(&self) -> u32 {
+        self.returns
+    }
+
+    pub fn high_score(&self, score: &Score) -> HighScore {
+        HighScore {
+            points: score.player1(),
+            returns: self.returns,
+            level: self.level,
+        }
+    }
+
+    pub fn display_text(&self, score: &Score, lives: u32) -> String {
+        format!(
+            "{} | Lives {}",
+            score.player1(),
+            lives.saturating_sub(score.player2())
+        )
+    }
+}
+
+/// Movement of [`PlayerType::External`](crate::core::settings::PlayerType::External)
+/// and [`PlayerType::Remote`](crate::core::settings::PlayerType::Remote) paddles, from
+/// -1 (full speed down) to 1 (full speed up).
+#[derive(Resource, Default, Clone, Copy)]
+pub struct ExternalInput {
+    left: f32,
+    right: f32,
+}
+
+impl ExternalInput {
+    pub fn set(&mut self, side: ScoreField, direction: f32) {
+        let direction = direction.clamp(-1., 1.);
+        match side {
+            ScoreField::Left => self.left = direction,
+            ScoreField::Right => self.right = direction,
+        }
+    }
+
+    pub fn direction(&self, side: ScoreField) -> f32 {
+        match side {
+            ScoreField::Left => self.left,
+            ScoreField::Right => self.right,
         }
     }
 }
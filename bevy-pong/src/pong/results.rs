@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::components::ScoreField;
+use super::resources::{MatchClock, Score, SurvivalRun};
+use crate::core::settings::{GameMode, GameSettings, PlayerType};
+
+/// Modes with recorded results, as named in them.
+pub const RESULT_MODES: [&str; 2] = ["match", "survival"];
+
+/// One finished match, as submitted to the leaderboard and logged with gameplay events.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MatchResult {
+    pub mode: String,
+    /// Left then right player: a human's name, or the computer or bot profile.
+    pub players: [String; 2],
+    pub score: [u32; 2],
+    /// Index into `players` of the winner, if anyone won.
+    pub winner: Option<usize>,
+    pub rules: String,
+    pub seconds: f64,
+    /// Unix time the match ended at.
+    pub finished_at: u64,
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn player_name(player: &PlayerType, side: usize, human_names: Option<&[String; 2]>) -> String {
+    match (player, human_names) {
+        (PlayerType::Human, Some(names)) => names[side].clone(),
+        (other, _) => other.to_string(),
+    }
+}
+
+/// The result of the match that just ended, or `None` in practice. Human players go
+/// by `human_names` when given, and as `human` otherwise; a survival opponent goes by
+/// the level `run` reached.
+pub fn match_result(
+    settings: &GameSettings,
+    score: &Score,
+    clock: &MatchClock,
+    run: &SurvivalRun,
+    human_names: Option<&[String; 2]>,
+) -> Option<MatchResult> {
+    let mode = match settings.get_mode() {
+        GameMode::Match => RESULT_MODES[0],
+        GameMode::Survival => RESULT_MODES[1],
+        GameMode::Practice => return None,
+    };
+    let winner = match score.forfeited() {
+        Some(ScoreField::Left) => Some(1),
+        Some(ScoreField::Right) => Some(0),
+        None if mode == RESULT_MODES[1] => None,
+        None if score.player1() > score.player2() => Some(0),
+        None if score.player2() > score.player1() => Some(1),
+        None => None,
+    };
+
+    let opponent = match settings.get_mode() {
+        GameMode::Survival => format!("survival AI (level {:.1})", run.level),
+        _ => player_name(settings.get_player2(), 1, human_names),
+    };
+
+    Some(MatchResult {
+        mode: mode.into(),
+        players: [
+            player_name(settings.get_player1(), 0, human_names),
+            opponent,
+        ],
+        score: [score.player1(), score.player2()],
+        winner,
+        rules: settings.describe_rules(),
+        seconds: clock.seconds(),
+        finished_at: unix_time(),
+    })
+}
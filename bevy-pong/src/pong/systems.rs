@@ -1,36 +1,61 @@
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use bevy_rapier2d::prelude::*;
 use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
 use leafwing_input_manager::prelude::*;
 
-use std::f32::consts::PI;
+use rand::Rng;
 
-use crate::core::controls::GameAction;
-use crate::core::settings::{Difficulty, GameSettings, PlayerType};
-use crate::core::states::PausedState;
+use crate::accessibility::{AccessibilitySettings, InputMode};
+use crate::core::controls::{ControlScheme, ControlSchemes, GameAction};
+use crate::core::high_scores::HighScores;
+use crate::core::rng::{GameRng, RngStream};
+use crate::core::settings::{
+    AiPresets, Difficulty, GameMode, GameSettings, PaddleMovement, PlayerType, PracticeOpponent,
+    PracticeSettings, SurvivalSettings,
+};
+use crate::core::states::{GameState, PausedState};
+use crate::theme::Themed;
 
 use super::components::*;
 use super::constants;
-use super::observers::OnPointScored;
+use super::observers::{OnBallStalled, OnPaddleHit, OnPointScored, OnWallBounce};
+use super::resources::{
+    ExternalInput, Launcher, MatchClock, PointerTargets, PracticeStats, SurvivalRun, WinConditions,
+};
 use super::Score;
 
 pub mod setup {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn game(
         mut commands: Commands,
-        windows: Query<&Window>,
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<ColorMaterial>>,
         mut score: ResMut<Score>,
+        mut clock: ResMut<MatchClock>,
+        mut rng: ResMut<GameRng>,
         mut next_state: ResMut<NextState<PausedState>>,
+        mut practice: ResMut<PracticeStats>,
+        mut launcher: ResMut<Launcher>,
+        mut run: ResMut<SurvivalRun>,
         settings: Res<GameSettings>,
+        survival: Res<SurvivalSettings>,
     ) {
-        score.reset();
+        score.reset(settings.get_points_to_win());
+        clock.reset(settings.mode_time_limit());
+        commands.insert_resource(WinConditions::for_settings(&settings, &survival));
+        practice.reset();
+        run.reset();
+        launcher.ticks_until_launch = 0;
+        *rng = GameRng::from_optional_seed(settings.get_seed());
         next_state.set(PausedState::Playing);
 
-        let window = windows.single();
-        let (width, height) = (window.resolution.width(), window.resolution.height());
+        let Vec2 {
+            x: width,
+            y: height,
+        } = settings.get_arena().size();
 
         spawn_game_world(
             &mut commands,
@@ -38,6 +63,7 @@ pub mod setup {
             height,
             &mut meshes,
             &mut materials,
+            super::ball::serve_velocity(&mut rng),
             settings,
         );
     }
@@ -48,33 +74,45 @@ pub mod setup {
         height: f32,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        serve: Vec2,
         settings: Res<GameSettings>,
     ) {
+        // Practice has no opponent: a wall replaces their goal and the launcher serves.
+        let practice = settings.get_mode() == GameMode::Practice;
+        let timed = settings.mode_time_limit().is_some();
+
         commands
             .spawn((Pong, Transform::default(), Visibility::default()))
             .with_children(|builder| {
-                create_board(builder, width, height, meshes, materials);
+                create_board(builder, width, height, meshes, materials, practice);
                 create_players(builder, width, meshes, materials, settings);
-                spawn_ball(builder, meshes, materials);
+                if !practice {
+                    serve_ball(builder, meshes, materials, serve, timed);
+                }
                 create_score(builder, height);
+                if timed {
+                    create_clock(builder, height);
+                }
             });
     }
 
-    fn create_wall(
-        commands: &mut ChildBuilder,
+    fn create_wall<'a>(
+        commands: &'a mut ChildBuilder,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
         width: f32,
         height: f32,
         transform: Transform,
-    ) {
+    ) -> EntityCommands<'a> {
         commands.spawn((
             Mesh2d(meshes.add(Rectangle::new(width, height))),
-            MeshMaterial2d(materials.add(Color::WHITE)),
+            MeshMaterial2d(materials.add(ColorMaterial::default())),
             transform,
             Collider::cuboid(width / 2.0, height / 2.0),
             RigidBody::Fixed,
-        ));
+            Wall,
+            Themed::Walls,
+        ))
     }
 
     fn create_board(
@@ -83,6 +121,7 @@ pub mod setup {
         screen_height: f32,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
+        rebound_right: bool,
     ) {
         // Create horizontal walls
         for y_pos in [
@@ -111,8 +150,23 @@ pub mod setup {
                 ScoreField::Right,
             ),
         ] {
+            let transform = Transform::from_xyz(x_pos, constants::TOP_BUFFER / -2.0, 0.0);
+
+            if rebound_right && score_field == ScoreField::Right {
+                create_wall(
+                    builder,
+                    meshes,
+                    materials,
+                    constants::WALL_THICKNESS,
+                    sensor_height,
+                    transform,
+                )
+                .insert(ReboundWall);
+                continue;
+            }
+
             builder.spawn((
-                Transform::from_xyz(x_pos, constants::TOP_BUFFER / -2.0, 0.0),
+                transform,
                 Collider::cuboid(constants::WALL_THICKNESS, sensor_height / 2.0),
                 Sensor,
                 score_field,
@@ -133,7 +187,7 @@ pub mod setup {
                 constants::paddle::WIDTH,
                 constants::paddle::HEIGHT,
             ))),
-            MeshMaterial2d(materials.add(Color::WHITE)),
+            MeshMaterial2d(materials.add(ColorMaterial::default())),
             transform,
             Collider::cuboid(
                 constants::paddle::WIDTH / 2.0,
@@ -141,8 +195,13 @@ pub mod setup {
             ),
             RigidBody::KinematicPositionBased,
             KinematicCharacterController::default(),
+            AiTarget::default(),
+            AimOffset::default(),
+            AiMemory::default(),
+            PaddleMotion::default(),
             player_type,
             score_field,
+            Themed::Paddle(score_field),
         ));
     }
 
@@ -165,12 +224,20 @@ pub mod setup {
                 ScoreField::Right,
             ),
         ] {
+            let player_type = match (settings.get_mode(), score_field) {
+                (GameMode::Practice, ScoreField::Right) => continue,
+                (GameMode::Survival, ScoreField::Right) => {
+                    PlayerType::Computer(Difficulty::Level(0.))
+                }
+                _ => *player_type,
+            };
+
             create_paddle(
                 builder,
                 meshes,
                 materials,
                 Transform::from_xyz(x_offset, constants::TOP_BUFFER / -2.0, 0.0),
-                player_type.clone(),
+                player_type,
                 score_field,
             );
         }
@@ -179,33 +246,56 @@ pub mod setup {
     fn create_score(builder: &mut ChildBuilder, window_height: f32) {
         builder.spawn((
             Text2d::new("0 - 0"),
-            TextColor(Color::WHITE),
-            TextFont {
-                font_size: 100.,
-                ..default()
-            },
             Transform::from_translation((window_height / 2.0 - 50.) * Vec3::Y),
+            ScoreText,
+            Themed::Score,
         ));
     }
 
-    pub fn spawn_ball(
-        builder: &mut ChildBuilder,
+    fn create_clock(builder: &mut ChildBuilder, window_height: f32) {
+        builder.spawn((
+            Text2d::default(),
+            Transform::from_translation((window_height / 2.0 - 115.) * Vec3::Y),
+            ClockText,
+            Themed::Clock,
+        ));
+    }
+
+    /// Spawns a ball for the next point; with `countdown` it waits at the centre first.
+    pub fn serve_ball<'a>(
+        builder: &'a mut ChildBuilder,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
-    ) {
+        velocity: Vec2,
+        countdown: bool,
+    ) -> EntityCommands<'a> {
+        if !countdown {
+            return spawn_ball(builder, meshes, materials, velocity);
+        }
+
+        let mut ball = spawn_ball(builder, meshes, materials, Vec2::ZERO);
+        ball.insert(ServeCountdown {
+            ticks: constants::game::SERVE_COUNTDOWN_TICKS,
+            velocity,
+        });
+        ball
+    }
+
+    pub fn spawn_ball<'a>(
+        builder: &'a mut ChildBuilder,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+        velocity: Vec2,
+    ) -> EntityCommands<'a> {
         builder.spawn((
             Mesh2d(meshes.add(Circle::new(constants::ball::RADIUS))),
-            MeshMaterial2d(materials.add(Color::WHITE)),
+            MeshMaterial2d(materials.add(ColorMaterial::default())),
             Ball,
+            Themed::Ball,
+            Rally::default(),
             RigidBody::Dynamic,
             Ccd::enabled(),
-            Velocity {
-                linvel: Vec2::new(
-                    constants::ball::INITIAL_VELOCITY.0,
-                    constants::ball::INITIAL_VELOCITY.1,
-                ),
-                angvel: 0.,
-            },
+            Velocity::linear(velocity),
             GravityScale(0.),
             Sleeping::disabled(),
             Collider::ball(constants::ball::RADIUS),
@@ -218,45 +308,204 @@ pub mod setup {
                 combine_rule: CoefficientCombineRule::Min,
             },
             ActiveEvents::COLLISION_EVENTS,
-        ));
+        ))
     }
 }
 
 pub mod movement {
     use super::*;
 
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub fn players(
         keys: Res<ActionState<GameAction>>,
+        schemes: Res<ControlSchemes>,
+        pointers: Res<PointerTargets>,
+        external: Res<ExternalInput>,
+        settings: Res<GameSettings>,
+        presets: Res<AiPresets>,
+        accessibility: Option<Res<AccessibilitySettings>>,
         mut players: Query<(
             &mut KinematicCharacterController,
+            &mut PaddleMotion,
+            (&mut AiTarget, &mut AiMemory, &AimOffset),
             &PlayerType,
             &Transform,
             &ScoreField,
         )>,
         balls: Query<&Transform, With<Ball>>,
     ) {
-        let ball = balls.single();
-
-        for (player, player_type, paddle_position, score_field) in players.iter_mut() {
-            match player_type {
-                PlayerType::Human => handle_player_input(player, score_field, &keys),
-                PlayerType::Computer(difficulty) => {
-                    handle_computer_movement(player, paddle_position, ball, *difficulty)
+        let movement = settings.get_movement();
+        let input_mode = accessibility.map_or(InputMode::Hold, |accessibility| accessibility.input);
+        let paddles: Vec<(ScoreField, f32)> = players
+            .iter()
+            .map(|(.., transform, field)| (*field, transform.translation.y))
+            .collect();
+
+        for (
+            mut player,
+            mut motion,
+            (target, mut memory, offset),
+            player_type,
+            paddle_position,
+            score_field,
+        ) in players.iter_mut()
+        {
+            let (scheme, pointer, dash) = match score_field {
+                ScoreField::Left => (schemes.player1, pointers.left, GameAction::Player1Dash),
+                ScoreField::Right => (schemes.player2, pointers.right, GameAction::Player2Dash),
+            };
+            let dash = keys.just_pressed(&dash);
+
+            let (desired, dash) = match (player_type, scheme) {
+                (PlayerType::Human, ControlScheme::Keyboard) => {
+                    let direction = match input_mode {
+                        InputMode::Hold => get_input_direction(score_field, &keys),
+                        InputMode::Toggle => {
+                            toggle_input_direction(&mut motion, score_field, &keys)
+                        }
+                    };
+                    (direction * movement.top_speed(), dash)
                 }
-            }
+                (PlayerType::Human, ControlScheme::Pointer) => (
+                    pointer.map_or(0., |pointer| {
+                        approach(
+                            pointer - paddle_position.translation.y,
+                            movement.top_speed(),
+                            &movement,
+                        )
+                    }),
+                    dash,
+                ),
+                (PlayerType::External | PlayerType::Remote, _) => (
+                    external.direction(*score_field) * movement.top_speed(),
+                    false,
+                ),
+                (PlayerType::Computer(difficulty), _) => {
+                    let Some(ball) = nearest_ball(&balls, paddle_position) else {
+                        continue;
+                    };
+                    let profile = presets.profile(difficulty);
+
+                    // React to where the ball was `reaction_ticks` ago.
+                    memory.0.push_back(ball.translation.y);
+                    while memory.0.len() > profile.reaction_ticks as usize + 1 {
+                        memory.0.pop_front();
+                    }
+                    let seen = memory.0[0];
+
+                    // Aim off centre so the ball leaves at an angle away from the opponent.
+                    let opponent = paddles
+                        .iter()
+                        .find(|(field, _)| field != score_field)
+                        .map_or(0., |(_, y)| *y);
+                    let away = if opponent >= seen { 1. } else { -1. };
+                    let angle = away * profile.aggression * constants::paddle::HEIGHT * 0.4;
+
+                    handle_computer_movement(
+                        target,
+                        paddle_position,
+                        seen + offset.0 + angle,
+                        profile.speed,
+                        &movement,
+                    )
+                }
+            };
+
+            let speed = apply_motion(&mut motion, &movement, desired, dash);
+            player.translation = Some(Vec2::new(0., speed));
         }
     }
 
-    fn handle_player_input(
-        mut player: Mut<KinematicCharacterController>,
-        score_field: &ScoreField,
-        keys: &Res<ActionState<GameAction>>,
+    /// Picks whichever ball is horizontally closest to the paddle.
+    fn nearest_ball<'a>(
+        balls: &'a Query<&Transform, With<Ball>>,
+        paddle: &Transform,
+    ) -> Option<&'a Transform> {
+        let distance = |ball: &Transform| (ball.translation.x - paddle.translation.x).abs();
+
+        balls
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
+
+    /// Speed that covers `distance` without overshooting, braking in time when
+    /// paddles have momentum.
+    fn approach(distance: f32, top_speed: f32, movement: &PaddleMovement) -> f32 {
+        let mut speed = distance.abs().min(top_speed);
+        if movement.momentum {
+            speed = speed.min((2. * movement.deceleration * distance.abs()).sqrt());
+        }
+
+        speed * distance.signum()
+    }
+
+    /// Moves the paddle's velocity towards `desired`, returning this tick's movement.
+    fn apply_motion(
+        motion: &mut PaddleMotion,
+        movement: &PaddleMovement,
+        desired: f32,
+        dash: bool,
+    ) -> f32 {
+        if !movement.momentum {
+            motion.velocity = desired;
+            return desired;
+        }
+
+        motion.cooldown = motion.cooldown.saturating_sub(1);
+        if dash && motion.cooldown == 0 && desired != 0. {
+            motion.dash_direction = desired.signum();
+            motion.dash_ticks = movement.dash_ticks;
+            motion.cooldown = movement.dash_cooldown;
+        }
+
+        if motion.dash_ticks > 0 {
+            motion.dash_ticks -= 1;
+            motion.velocity = motion.dash_direction * movement.dash_speed;
+            return motion.velocity;
+        }
+
+        let speeding_up = desired.abs() > motion.velocity.abs()
+            && (motion.velocity == 0. || desired.signum() == motion.velocity.signum());
+        let rate = if speeding_up {
+            movement.acceleration
+        } else {
+            movement.deceleration
+        };
+
+        motion.velocity += (desired - motion.velocity).clamp(-rate, rate);
+        motion.velocity
+    }
+
+    /// Converts the cursor and touches to world heights; a touch steers the paddle
+    /// on its half of the screen and takes precedence over the cursor.
+    pub fn track_pointers(
+        windows: Query<&Window, With<PrimaryWindow>>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        touches: Res<Touches>,
+        mut targets: ResMut<PointerTargets>,
     ) {
-        let direction = Vec2::new(
-            0.,
-            get_input_direction(score_field, keys) * constants::paddle::SPEED,
-        );
-        player.translation = Some(direction);
+        let (Ok(window), Ok((camera, camera_transform))) =
+            (windows.get_single(), cameras.get_single())
+        else {
+            return;
+        };
+        let to_world = |position: Vec2| {
+            camera
+                .viewport_to_world_2d(camera_transform, position)
+                .ok()
+                .map(|point| point.y)
+        };
+
+        let cursor = window.cursor_position().and_then(to_world);
+        let touch_on = |left: bool| {
+            touches
+                .iter()
+                .find(|touch| (touch.position().x < window.width() / 2.) == left)
+                .and_then(|touch| to_world(touch.position()))
+        };
+
+        targets.left = touch_on(true).or(cursor);
+        targets.right = touch_on(false).or(cursor);
     }
 
     fn get_input_direction(score_field: &ScoreField, keys: &Res<ActionState<GameAction>>) -> f32 {
@@ -284,21 +533,56 @@ pub mod movement {
         direction
     }
 
+    /// Starts the paddle moving in a direction when its key is pressed, and stops it
+    /// when the same key is pressed again.
+    fn toggle_input_direction(
+        motion: &mut PaddleMotion,
+        score_field: &ScoreField,
+        keys: &Res<ActionState<GameAction>>,
+    ) -> f32 {
+        let (up, down) = match score_field {
+            ScoreField::Left => (GameAction::Player1Up, GameAction::Player1Down),
+            ScoreField::Right => (GameAction::Player2Up, GameAction::Player2Down),
+        };
+
+        for (action, direction) in [(up, 1.), (down, -1.)] {
+            if keys.just_pressed(&action) {
+                motion.toggled = if motion.toggled == direction {
+                    0.
+                } else {
+                    direction
+                };
+            }
+        }
+        motion.toggled
+    }
+
+    /// Returns the AI's desired speed and whether it wants to dash.
     fn handle_computer_movement(
-        mut player: Mut<KinematicCharacterController>,
+        mut target: Mut<AiTarget>,
         paddle_position: &Transform,
-        ball: &Transform,
-        difficulty: Difficulty,
-    ) {
-        let direction = Vec2::new(0.0, ball.translation.y - paddle_position.translation.y);
-
-        player.translation = Some(direction.clamp_length_max(difficulty.speed()));
+        aim: f32,
+        speed: f32,
+        movement: &PaddleMovement,
+    ) -> (f32, bool) {
+        target.0 = Vec2::new(paddle_position.translation.x, aim);
+        let distance = target.0.y - paddle_position.translation.y;
+        let top_speed = speed * movement.top_speed() / constants::paddle::SPEED;
+
+        (
+            approach(distance, top_speed, movement),
+            distance.abs() > constants::paddle::HEIGHT,
+        )
     }
 }
 
 pub mod scoring {
     use super::*;
 
+    pub fn in_match(settings: Res<GameSettings>) -> bool {
+        settings.get_mode() == GameMode::Match
+    }
+
     pub fn detect_point(
         mut commands: Commands,
         mut collision_events: EventReader<CollisionEvent>,
@@ -323,65 +607,326 @@ pub mod scoring {
         }
     }
 
-    pub fn update_display(score: Res<Score>, mut score_text: Query<&mut Text2d>) {
+    pub fn update_display(score: Res<Score>, mut score_text: Query<&mut Text2d, With<ScoreText>>) {
         if score.is_changed() {
             for mut text in &mut score_text {
                 text.0 = score.display_text();
             }
         }
     }
+
+    pub fn update_clock_display(
+        clock: Res<MatchClock>,
+        mut clock_text: Query<&mut Text2d, With<ClockText>>,
+    ) {
+        let display = clock.display_text();
+        for mut text in &mut clock_text {
+            if text.0 != display {
+                text.0 = display.clone();
+            }
+        }
+    }
+
+    /// Ends the match once any of its win conditions holds, such as time running out.
+    pub fn check_win_conditions(
+        score: Res<Score>,
+        clock: Res<MatchClock>,
+        conditions: Res<WinConditions>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        if conditions.is_met(&score, &clock) {
+            next_state.set(GameState::Endgame);
+        }
+    }
 }
 
 pub mod ball {
     use super::*;
-    pub fn speed_up(
+
+    /// Launches the ball towards a random side at a random angle.
+    pub fn serve_velocity(rng: &mut GameRng) -> Vec2 {
+        let rng = rng.stream(RngStream::Serve);
+        let angle =
+            rng.gen_range(-constants::ball::MAX_SERVE_ANGLE..=constants::ball::MAX_SERVE_ANGLE);
+        let direction = if rng.gen_bool(0.5) { 1. } else { -1. };
+
+        Vec2::new(direction * angle.cos(), angle.sin()) * constants::ball::SERVE_SPEED
+    }
+
+    /// Keeps the ball heading for a goal: its angle from the horizontal is capped
+    /// and it always keeps a minimum horizontal speed.
+    pub fn constrain_velocity(velocity: Vec2) -> Vec2 {
+        let direction = if velocity.x < 0. { -1. } else { 1. };
+        let angle = velocity.y.atan2(velocity.x.abs()).clamp(
+            -constants::ball::MAX_BOUNCE_ANGLE,
+            constants::ball::MAX_BOUNCE_ANGLE,
+        );
+        let speed = velocity.length().min(constants::ball::MAX_BALL_SPEED);
+        let horizontal = (speed * angle.cos()).max(constants::ball::MIN_HORIZONTAL_SPEED);
+
+        Vec2::new(direction * horizontal, horizontal * angle.tan())
+    }
+
+    /// Holds served balls still until their countdown ends, then releases them.
+    pub fn count_down_serves(
+        mut commands: Commands,
+        mut balls: Query<(Entity, &mut Velocity, &mut ServeCountdown), With<Ball>>,
+    ) {
+        for (ball, mut velocity, mut countdown) in balls.iter_mut() {
+            countdown.ticks = countdown.ticks.saturating_sub(1);
+            if countdown.ticks == 0 {
+                velocity.linvel = countdown.velocity;
+                commands.entity(ball).remove::<ServeCountdown>();
+            } else {
+                velocity.linvel = Vec2::ZERO;
+            }
+        }
+    }
+
+    pub fn enforce_limits(mut balls: Query<&mut Velocity, (With<Ball>, Without<ServeCountdown>)>) {
+        for mut velocity in balls.iter_mut() {
+            let constrained = constrain_velocity(velocity.linvel);
+            if constrained != velocity.linvel {
+                velocity.linvel = constrained;
+            }
+        }
+    }
+
+    pub fn paddle_collision(
+        mut commands: Commands,
         mut collision_events: EventReader<CollisionEvent>,
-        mut velocities: Query<&mut Velocity>,
+        mut ball_query: Query<(&Transform, &mut Velocity, &mut Rally), With<Ball>>,
+        paddle_query: Query<(&Transform, &ScoreField), With<PlayerType>>,
     ) {
         for event in collision_events.read() {
             if let CollisionEvent::Started(entity1, entity2, _) = event {
-                if let Ok(mut velocity) = velocities.get_mut(*entity1) {
-                    adjust_velocity(&mut velocity);
-                } else if let Ok(mut velocity) = velocities.get_mut(*entity2) {
-                    adjust_velocity(&mut velocity);
+                let ((paddle, field), ball) =
+                    match (paddle_query.get(*entity1), paddle_query.get(*entity2)) {
+                        (Ok(paddle), _) => (paddle, *entity2),
+                        (_, Ok(paddle)) => (paddle, *entity1),
+                        _ => continue,
+                    };
+
+                if let Ok((ball_transform, mut ball_velocity, mut rally)) = ball_query.get_mut(ball)
+                {
+                    let hit_position = ((ball_transform.translation.y - paddle.translation.y)
+                        / (constants::paddle::HEIGHT * paddle.scale.y / 2.0))
+                        .clamp(-1., 1.);
+                    let angle = hit_position * constants::ball::MAX_BOUNCE_ANGLE;
+                    let speed = (ball_velocity.linvel.length() * constants::ball::SPEED_INCREASE)
+                        .min(constants::ball::MAX_BALL_SPEED);
+
+                    // Always send the ball away from the paddle's own goal, whichever way
+                    // the physics step already deflected it.
+                    let direction = match field {
+                        ScoreField::Left => 1.,
+                        ScoreField::Right => -1.,
+                    };
+                    ball_velocity.linvel = Vec2::new(direction * angle.cos(), angle.sin()) * speed;
+                    rally.ticks_since_hit = 0;
+                    commands.entity(ball).remove::<Spin>();
+
+                    commands.trigger(OnPaddleHit {
+                        side: *field,
+                        speed,
+                        offset: hit_position,
+                        position: ball_transform.translation.truncate(),
+                    });
                 }
             }
         }
     }
 
-    fn adjust_velocity(velocity: &mut Velocity) {
-        velocity.linvel.y *= constants::ball::SPEED_INCREASE;
-        velocity.linvel = velocity
-            .linvel
-            .clamp_length_max(constants::ball::MAX_BALL_SPEED);
-    }
-
-    pub fn paddle_collision(
+    pub fn wall_collision(
+        mut commands: Commands,
         mut collision_events: EventReader<CollisionEvent>,
-        mut ball_query: Query<(&Transform, &mut Velocity), With<Ball>>,
-        paddle_query: Query<&Transform, With<PlayerType>>,
+        ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+        wall_query: Query<(), With<Wall>>,
     ) {
         for event in collision_events.read() {
             if let CollisionEvent::Started(entity1, entity2, _) = event {
-                if let Ok(paddle) = paddle_query
+                let ball = ball_query
                     .get(*entity1)
-                    .or_else(|_| paddle_query.get(*entity2))
-                {
-                    let (ball_transform, mut ball_velocity) = ball_query.single_mut();
+                    .or_else(|_| ball_query.get(*entity2));
+                let is_wall = wall_query.contains(*entity1) || wall_query.contains(*entity2);
+
+                if let (Ok((transform, velocity)), true) = (ball, is_wall) {
+                    commands.trigger(OnWallBounce {
+                        speed: velocity.linvel.length(),
+                        position: transform.translation.truncate(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Re-serves balls that stopped reaching paddles or escaped the arena, and
+    /// awards the point when a ball slips past a goal sensor. Past a side without a
+    /// goal, such as the open end in practice, the ball is served again.
+    #[allow(clippy::type_complexity)]
+    pub fn detect_stall(
+        mut commands: Commands,
+        settings: Res<GameSettings>,
+        mut balls: Query<(Entity, &Transform, &mut Rally), (With<Ball>, Without<ServeCountdown>)>,
+        goals: Query<(Entity, &ScoreField), Without<PlayerType>>,
+    ) {
+        let half_size = settings.get_arena().size() / 2.;
+
+        for (ball, transform, mut rally) in balls.iter_mut() {
+            rally.ticks_since_hit += 1;
+            let position = transform.translation;
+
+            if position.x.abs() > half_size.x {
+                let side = if position.x < 0. {
+                    ScoreField::Left
+                } else {
+                    ScoreField::Right
+                };
+                match goals.iter().find(|(_, field)| **field == side) {
+                    Some((goal, _)) => commands.trigger(OnPointScored(goal)),
+                    None => commands.trigger(OnBallStalled(ball)),
+                }
+            } else if position.y > half_size.y - constants::TOP_BUFFER
+                || position.y < -half_size.y
+                || rally.ticks_since_hit > constants::ball::STALL_TICKS
+            {
+                commands.trigger(OnBallStalled(ball));
+            }
+        }
+    }
+}
 
-                    let hit_position = (ball_transform.translation.y - paddle.translation.y)
-                        / (constants::paddle::HEIGHT / 2.0);
-                    let angle = hit_position * PI / 2.0;
-                    let speed = ball_velocity.linvel.length();
+pub mod practice {
+    use super::*;
 
-                    ball_velocity.linvel.x = -ball_velocity.linvel.x;
-                    ball_velocity.linvel.y = angle * speed;
+    pub fn in_practice(settings: Res<GameSettings>) -> bool {
+        settings.get_mode() == GameMode::Practice
+    }
 
-                    ball_velocity.linvel = ball_velocity.linvel.normalize() * speed;
+    /// Serves from the rebound wall: whenever no ball is in play against the wall,
+    /// or every interval with the launcher.
+    #[allow(clippy::too_many_arguments)]
+    pub fn launch_balls(
+        mut commands: Commands,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut rng: ResMut<GameRng>,
+        mut launcher: ResMut<Launcher>,
+        practice: Res<PracticeSettings>,
+        settings: Res<GameSettings>,
+        balls: Query<(), With<Ball>>,
+        pong: Query<Entity, With<Pong>>,
+    ) {
+        launcher.ticks_until_launch = launcher.ticks_until_launch.saturating_sub(1);
+        let due = match practice.opponent {
+            PracticeOpponent::Wall => balls.is_empty(),
+            PracticeOpponent::Launcher => launcher.ticks_until_launch == 0,
+        };
+        let Ok(pong) = pong.get_single() else {
+            return;
+        };
+        if !due {
+            return;
+        }
+
+        launcher.ticks_until_launch = (practice.interval as f64 * constants::TICK_RATE) as u32;
+
+        let half_size = settings.get_arena().size() / 2.;
+        let max_angle = practice
+            .angle_range
+            .to_radians()
+            .min(constants::ball::MAX_BOUNCE_ANGLE);
+        let rng = rng.stream(RngStream::Serve);
+        let angle = rng.gen_range(-max_angle..=max_angle);
+        let height = rng.gen_range(
+            -half_size.y + constants::paddle::HEIGHT
+                ..=half_size.y - constants::TOP_BUFFER - constants::paddle::HEIGHT,
+        );
+        let velocity = Vec2::new(-angle.cos(), angle.sin()) * practice.serve_speed;
+        let position = Vec3::new(half_size.x - constants::paddle::BUFFER, height, 0.);
+
+        commands.entity(pong).with_children(|parent| {
+            super::setup::spawn_ball(parent, &mut meshes, &mut materials, velocity)
+                .insert((Transform::from_translation(position), Spin(practice.spin)));
+        });
+    }
+
+    pub fn apply_spin(mut balls: Query<(&mut Velocity, &Spin), With<Ball>>) {
+        for (mut velocity, spin) in balls.iter_mut() {
+            velocity.linvel.y += spin.0 / constants::TICK_RATE as f32;
+        }
+    }
+
+    /// Removes returned balls when they reach the launcher, so only fresh serves come back.
+    pub fn absorb_returns(
+        mut commands: Commands,
+        mut collision_events: EventReader<CollisionEvent>,
+        practice: Res<PracticeSettings>,
+        walls: Query<(), With<ReboundWall>>,
+        balls: Query<(), With<Ball>>,
+    ) {
+        if practice.opponent != PracticeOpponent::Launcher {
+            return;
+        }
+
+        for event in collision_events.read() {
+            if let CollisionEvent::Started(entity1, entity2, _) = event {
+                for (wall, ball) in [(entity1, entity2), (entity2, entity1)] {
+                    if walls.contains(*wall) && balls.contains(*ball) {
+                        commands.entity(*ball).despawn();
+                    }
                 }
             }
         }
     }
+
+    pub fn update_display(
+        stats: Res<PracticeStats>,
+        mut score_text: Query<&mut Text2d, With<ScoreText>>,
+    ) {
+        if stats.is_changed() {
+            for mut text in &mut score_text {
+                text.0 = stats.display_text();
+            }
+        }
+    }
+}
+
+pub mod survival {
+    use super::*;
+
+    pub fn in_survival(settings: Res<GameSettings>) -> bool {
+        settings.get_mode() == GameMode::Survival
+    }
+
+    pub fn update_display(
+        score: Res<Score>,
+        run: Res<SurvivalRun>,
+        survival: Res<SurvivalSettings>,
+        mut score_text: Query<&mut Text2d, With<ScoreText>>,
+    ) {
+        if score.is_changed() || run.is_changed() {
+            for mut text in &mut score_text {
+                text.0 = run.display_text(&score, survival.lives);
+            }
+        }
+    }
+
+    /// Enters the finished run into the high score table.
+    pub fn record_high_score(
+        score: Res<Score>,
+        mut run: ResMut<SurvivalRun>,
+        mut high_scores: ResMut<HighScores>,
+    ) {
+        run.rank = high_scores.record(run.high_score(&score));
+    }
+}
+
+/// Advances the clock while the ball is in play; pauses and serve countdowns don't count.
+pub fn tick_match_clock(mut clock: ResMut<MatchClock>, serving: Query<(), With<ServeCountdown>>) {
+    if serving.is_empty() {
+        clock.tick();
+    }
 }
 
 pub fn cleanup_game(mut commands: Commands, pong: Query<Entity, With<Pong>>) {
@@ -390,7 +935,18 @@ pub fn cleanup_game(mut commands: Commands, pong: Query<Entity, With<Pong>>) {
     }
 }
 
-pub use ball::{paddle_collision as ball_paddle_collision, speed_up as speed_up_ball};
-pub use movement::players as move_players;
-pub use scoring::{detect_point, update_display as update_score_display};
+pub use ball::{
+    count_down_serves, detect_stall as detect_ball_stall, enforce_limits as enforce_ball_limits,
+    paddle_collision as ball_paddle_collision, wall_collision as ball_wall_collision,
+};
+pub use movement::{players as move_players, track_pointers};
+pub use practice::{
+    absorb_returns, apply_spin, in_practice, launch_balls,
+    update_display as update_practice_display,
+};
+pub use scoring::{
+    check_win_conditions, detect_point, in_match, update_clock_display,
+    update_display as update_score_display,
+};
 pub use setup::game as setup_game;
+pub use survival::{in_survival, record_high_score, update_display as update_survival_display};
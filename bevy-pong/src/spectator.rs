@@ -0,0 +1,620 @@
+//! Viewers of a running game, and players joining it from another machine.
+//!
+//! A host streams one JSON line per fixed tick to every connected viewer. Each line is
+//! a complete [`MatchFrame`], so a viewer joining late is up to date from its first
+//! line, which is the host's latest frame sent straight after connecting. Viewers
+//! draw the frames a short, fixed delay after receiving them, which smooths out
+//! network jitter.
+//!
+//! A side played by [`PlayerType::Remote`] is open to the first viewer that sends
+//! `join left` or `join right`. That viewer then steers the paddle with one line per
+//! change of direction, in the form bots reply in (`up`, `down`, `stay` or a number
+//! from -1 to 1), and the side opens again when it disconnects.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Collider;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::parse_reply;
+use crate::core::controls::GameAction;
+use crate::core::settings::{GameSettings, PlayerType};
+use crate::pong::constants::ball;
+use crate::pong::{Ball, ClockText, ExternalInput, MatchClock, ScoreField, ScoreText, Wall};
+use crate::theme::Themed;
+
+/// Frames a viewer may fall behind before the host drops it.
+const VIEWER_BACKLOG: usize = 256;
+/// Direction changes a joined player may queue up before new ones are skipped.
+const INPUT_BACKLOG: usize = 16;
+/// How often a viewer retries a host it can't reach.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+pub const DEFAULT_DELAY_MS: u64 = 250;
+const SIDES: [ScoreField; 2] = [ScoreField::Left, ScoreField::Right];
+
+/// A rectangle as centre x, centre y, width and height.
+pub type Rect4 = [f32; 4];
+
+/// Everything a viewer needs to draw one tick of the match.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct MatchFrame {
+    pub tick: u64,
+    pub width: f32,
+    pub height: f32,
+    pub score: String,
+    pub clock: String,
+    pub walls: Vec<Rect4>,
+    pub paddles: Vec<Rect4>,
+    pub balls: Vec<[f32; 2]>,
+}
+
+fn rect(transform: &Transform, collider: &Collider) -> Option<Rect4> {
+    let half = collider.as_cuboid()?.half_extents() * transform.scale.truncate();
+    Some([
+        transform.translation.x,
+        transform.translation.y,
+        half.x * 2.,
+        half.y * 2.,
+    ])
+}
+
+#[allow(clippy::type_complexity)]
+fn capture_frame(
+    settings: Res<GameSettings>,
+    clock: Res<MatchClock>,
+    walls: Query<(&Transform, &Collider), With<Wall>>,
+    paddles: Query<(&Transform, &Collider), With<PlayerType>>,
+    balls: Query<&Transform, With<Ball>>,
+    score_text: Query<&Text2d, With<ScoreText>>,
+    clock_text: Query<&Text2d, With<ClockText>>,
+) -> MatchFrame {
+    let size = settings.get_arena().size();
+
+    MatchFrame {
+        tick: clock.ticks(),
+        width: size.x,
+        height: size.y,
+        score: score_text
+            .iter()
+            .next()
+            .map(|text| text.0.clone())
+            .unwrap_or_default(),
+        clock: clock_text
+            .iter()
+            .next()
+            .map(|text| text.0.clone())
+            .unwrap_or_default(),
+        walls: walls.iter().filter_map(|(t, c)| rect(t, c)).collect(),
+        paddles: paddles.iter().filter_map(|(t, c)| rect(t, c)).collect(),
+        balls: balls
+            .iter()
+            .map(|t| [t.translation.x, t.translation.y])
+            .collect(),
+    }
+}
+
+fn side_name(side: ScoreField) -> &'static str {
+    match side {
+        ScoreField::Left => "left",
+        ScoreField::Right => "right",
+    }
+}
+
+/// Connected viewers of this game, each fed by its own writer thread, and which of
+/// them play the open sides.
+#[derive(Resource)]
+pub struct SpectatorHost {
+    joining: Mutex<Receiver<TcpStream>>,
+    /// Lines sent by viewers, with `None` once a viewer's connection closes.
+    messages: Mutex<Receiver<(u64, Option<String>)>>,
+    sender: Sender<(u64, Option<String>)>,
+    viewers: Vec<SyncSender<String>>,
+    next_id: u64,
+    /// Viewers playing the left and right side.
+    seats: [Option<u64>; 2],
+    latest: Option<String>,
+    port: u16,
+}
+
+impl SpectatorHost {
+    pub fn listen(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let port = listener.local_addr()?.port();
+        let (sender, joining) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if sender.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+        let (sender, messages) = mpsc::channel();
+
+        Ok(Self {
+            joining: Mutex::new(joining),
+            messages: Mutex::new(messages),
+            sender,
+            viewers: Vec::new(),
+            next_id: 0,
+            seats: [None; 2],
+            latest: None,
+            port,
+        })
+    }
+
+    /// Port viewers connect to, which the system picks when listening on port 0.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn viewer_count(&self) -> usize {
+        self.viewers.len()
+    }
+
+    /// Whether a viewer plays `side`.
+    pub fn seat_taken(&self, side: ScoreField) -> bool {
+        self.seats[side as usize].is_some()
+    }
+
+    fn add_viewer(&mut self, mut stream: TcpStream) {
+        let _ = stream.set_nodelay(true);
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if let Ok(reader) = stream.try_clone() {
+            let sender = self.sender.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(reader).lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send((id, Some(line))).is_err() {
+                        return;
+                    }
+                }
+                let _ = sender.send((id, None));
+            });
+        }
+
+        let (sender, frames) = mpsc::sync_channel::<String>(VIEWER_BACKLOG);
+        thread::spawn(move || {
+            for frame in frames {
+                if writeln!(stream, "{}", frame).is_err() {
+                    break;
+                }
+            }
+            // Ends the reader too, which gives up the viewer's seat.
+            let _ = stream.shutdown(Shutdown::Both);
+        });
+
+        if let Some(latest) = &self.latest {
+            let _ = sender.try_send(latest.clone());
+        }
+        self.viewers.push(sender);
+    }
+
+    /// Sends `frame` to every viewer, dropping the ones that left or fell too far behind.
+    fn broadcast(&mut self, frame: String) {
+        self.viewers
+            .retain(|viewer| match viewer.try_send(frame.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+            });
+        self.latest = Some(frame);
+    }
+
+    /// Seats viewers that ask for one of the `open` sides, passes on the moves of
+    /// seated players and frees the seats of players that left. A side that stops
+    /// being open frees its seat as well.
+    pub fn update_seats(&mut self, open: [bool; 2], input: &mut ExternalInput) {
+        let messages: Vec<(u64, Option<String>)> = match self.messages.get_mut() {
+            Ok(messages) => messages.try_iter().collect(),
+            Err(_) => Vec::new(),
+        };
+
+        for (id, message) in messages {
+            let seat = self.seats.iter().position(|seat| *seat == Some(id));
+            match (message, seat) {
+                (None, Some(seat)) => {
+                    info!("The {} player left", side_name(SIDES[seat]));
+                    self.seats[seat] = None;
+                    input.set(SIDES[seat], 0.);
+                }
+                (Some(line), None) => {
+                    let Some(side) = line
+                        .trim()
+                        .strip_prefix("join ")
+                        .and_then(|name| SIDES.into_iter().find(|side| side_name(*side) == name))
+                    else {
+                        continue;
+                    };
+                    let seat = side as usize;
+                    if open[seat] && self.seats[seat].is_none() {
+                        info!("A player joined on the {} side", side_name(side));
+                        self.seats[seat] = Some(id);
+                        input.set(side, 0.);
+                    }
+                }
+                (Some(line), Some(seat)) => {
+                    if let Some(direction) = parse_reply(&line) {
+                        input.set(SIDES[seat], direction);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        for (seat, open) in open.into_iter().enumerate() {
+            if !open {
+                self.seats[seat] = None;
+            }
+        }
+    }
+}
+
+fn stream_frame(In(frame): In<MatchFrame>, mut host: ResMut<SpectatorHost>) {
+    let joining: Vec<TcpStream> = match host.joining.get_mut() {
+        Ok(joining) => joining.try_iter().collect(),
+        Err(_) => Vec::new(),
+    };
+    let Ok(line) = serde_json::to_string(&frame) else {
+        return;
+    };
+    host.broadcast(line);
+    for stream in joining {
+        info!("Spectator joined from {:?}", stream.peer_addr().ok());
+        host.add_viewer(stream);
+    }
+}
+
+fn seat_players(
+    settings: Res<GameSettings>,
+    mut host: ResMut<SpectatorHost>,
+    mut input: ResMut<ExternalInput>,
+) {
+    let open = [settings.get_player1(), settings.get_player2()]
+        .map(|player| *player == PlayerType::Remote);
+    host.update_seats(open, &mut input);
+}
+
+/// Streams this game to viewers connecting on `port`, and seats the ones that join
+/// an open side.
+pub struct SpectatorHostPlugin {
+    pub port: u16,
+}
+
+impl Plugin for SpectatorHostPlugin {
+    fn build(&self, app: &mut App) {
+        match SpectatorHost::listen(self.port) {
+            Ok(host) => {
+                info!("Streaming to spectators on port {}", host.port());
+                app.insert_resource(host)
+                    .add_systems(FixedPreUpdate, seat_players)
+                    .add_systems(FixedPostUpdate, capture_frame.pipe(stream_frame));
+            }
+            Err(error) => warn!("Could not stream on port {}: {}", self.port, error),
+        }
+    }
+}
+
+/// Frames received from the host, waiting out the viewing delay, and the moves of
+/// the side this viewer plays if it joined as a player.
+#[derive(Resource)]
+pub struct SpectatorFeed {
+    address: String,
+    delay: Duration,
+    seat: Option<ScoreField>,
+    /// Direction lines for the host, written by the connection's own thread.
+    moves: Option<SyncSender<String>>,
+    /// Last direction handed to the connection.
+    direction: f32,
+    /// Frames from the host, with `None` once the connection closes.
+    frames: Mutex<Receiver<Option<MatchFrame>>>,
+    sender: Sender<Option<MatchFrame>>,
+    buffer: VecDeque<(Instant, MatchFrame)>,
+    /// Whether a connection is open or being opened.
+    connected: bool,
+    next_attempt: Instant,
+    shown: Option<MatchFrame>,
+}
+
+impl SpectatorFeed {
+    pub fn new(address: String, delay: Duration) -> Self {
+        let (sender, frames) = mpsc::channel();
+        Self {
+            address,
+            delay,
+            seat: None,
+            moves: None,
+            direction: 0.,
+            frames: Mutex::new(frames),
+            sender,
+            buffer: VecDeque::new(),
+            connected: false,
+            next_attempt: Instant::now(),
+            shown: None,
+        }
+    }
+
+    /// Asks the host for `side` on connecting, to play it rather than only watch.
+    pub fn joining(mut self, side: ScoreField) -> Self {
+        self.seat = Some(side);
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Side this viewer asked to play.
+    pub fn seat(&self) -> Option<ScoreField> {
+        self.seat
+    }
+
+    /// Moves the joined paddle from -1 (full speed down) to 1 (full speed up). Only
+    /// changes go out, and are skipped while the connection is backed up.
+    pub fn steer(&mut self, direction: f32) {
+        if direction == self.direction {
+            return;
+        }
+        if let Some(moves) = &self.moves {
+            if moves.try_send(direction.to_string()).is_ok() {
+                self.direction = direction;
+            }
+        }
+    }
+
+    /// Frame due on screen now, if any arrived at least `delay` ago.
+    pub fn current(&self) -> Option<&MatchFrame> {
+        self.shown.as_ref()
+    }
+
+    /// Connects on another thread, which reports `None` when it can't connect or the
+    /// connection closes. A joining viewer asks for its side first thing, then has
+    /// its moves written by one more thread.
+    fn connect(&mut self) {
+        self.connected = true;
+        let address = self.address.clone();
+        let sender = self.sender.clone();
+        let seat = self.seat;
+        let (moves, lines) = mpsc::sync_channel::<String>(INPUT_BACKLOG);
+        self.moves = seat.map(|_| moves);
+        self.direction = 0.;
+        thread::spawn(move || {
+            let Ok(stream) = TcpStream::connect(&address) else {
+                let _ = sender.send(None);
+                return;
+            };
+            if let (Some(side), Ok(mut writer)) = (seat, stream.try_clone()) {
+                thread::spawn(move || {
+                    let _ = writeln!(writer, "join {}", side_name(side));
+                    for line in lines {
+                        if writeln!(writer, "{}", line).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                if let Ok(frame) = serde_json::from_str(&line) {
+                    if sender.send(Some(frame)).is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = sender.send(None);
+        });
+    }
+
+    /// Takes in newly received frames and advances the one on screen.
+    pub fn update(&mut self, now: Instant) {
+        if !self.connected && now >= self.next_attempt {
+            self.next_attempt = now + RECONNECT_INTERVAL;
+            self.connect();
+        }
+
+        if let Ok(frames) = self.frames.get_mut() {
+            for frame in frames.try_iter() {
+                match frame {
+                    Some(frame) => self.buffer.push_back((now, frame)),
+                    None => self.connected = false,
+                }
+            }
+        }
+        while let Some((received, _)) = self.buffer.front() {
+            if now.duration_since(*received) < self.delay {
+                break;
+            }
+            self.shown = self.buffer.pop_front().map(|(_, frame)| frame);
+        }
+    }
+}
+
+/// Everything drawn for the feed, removed again with it.
+#[derive(Component)]
+struct SpectatorView;
+
+#[derive(Component)]
+struct ViewRect;
+
+#[derive(Component)]
+struct ViewBall;
+
+#[derive(Component)]
+struct ViewScore;
+
+#[derive(Component)]
+struct ViewClock;
+
+#[derive(Resource)]
+struct ViewMeshes {
+    rect: Handle<Mesh>,
+    ball: Handle<Mesh>,
+}
+
+fn setup_view_meshes(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ViewMeshes {
+        rect: meshes.add(Rectangle::new(1., 1.)),
+        ball: meshes.add(Circle::new(ball::RADIUS)),
+    });
+}
+
+fn spawn_view(mut commands: Commands) {
+    commands.spawn((Text2d::default(), ViewScore, Themed::Score, SpectatorView));
+    commands.spawn((Text2d::default(), ViewClock, Themed::Clock, SpectatorView));
+}
+
+fn despawn_view(mut commands: Commands, view: Query<Entity, With<SpectatorView>>) {
+    for entity in &view {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Matches the number of `existing` entities to `count`, spawning or despawning
+/// the difference.
+fn resize_pool(
+    commands: &mut Commands,
+    existing: &[Entity],
+    count: usize,
+    mut spawn: impl FnMut(&mut Commands),
+) {
+    for entity in existing.iter().skip(count) {
+        commands.entity(*entity).despawn();
+    }
+    for _ in existing.len()..count {
+        spawn(commands);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn draw_frame(
+    mut commands: Commands,
+    mut feed: ResMut<SpectatorFeed>,
+    view: Res<ViewMeshes>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rects: Query<(Entity, &mut Transform, &mut Themed), (With<ViewRect>, Without<ViewBall>)>,
+    mut balls: Query<(Entity, &mut Transform), (With<ViewBall>, Without<ViewRect>)>,
+    mut score: Query<
+        (&mut Text2d, &mut Transform),
+        (
+            With<ViewScore>,
+            Without<ViewRect>,
+            Without<ViewBall>,
+            Without<ViewClock>,
+        ),
+    >,
+    mut clock: Query<
+        (&mut Text2d, &mut Transform),
+        (
+            With<ViewClock>,
+            Without<ViewRect>,
+            Without<ViewBall>,
+            Without<ViewScore>,
+        ),
+    >,
+) {
+    feed.update(Instant::now());
+    let waiting = format!("Waiting for {}", feed.address);
+    let Some(frame) = feed.current() else {
+        for (mut text, _) in &mut clock {
+            text.0.clone_from(&waiting);
+        }
+        return;
+    };
+
+    let paddle = |[x, ..]: &Rect4| {
+        Themed::Paddle(if *x < 0. {
+            ScoreField::Left
+        } else {
+            ScoreField::Right
+        })
+    };
+    let shapes: Vec<(Rect4, Themed)> = (frame.walls.iter().map(|wall| (*wall, Themed::Walls)))
+        .chain(frame.paddles.iter().map(|rect| (*rect, paddle(rect))))
+        .collect();
+    let existing: Vec<Entity> = rects.iter().map(|(entity, ..)| entity).collect();
+    resize_pool(&mut commands, &existing, shapes.len(), |commands| {
+        commands.spawn((
+            Mesh2d(view.rect.clone()),
+            MeshMaterial2d(materials.add(ColorMaterial::default())),
+            Transform::default(),
+            Themed::Walls,
+            ViewRect,
+            SpectatorView,
+        ));
+    });
+    for ((_, mut transform, mut themed), ([x, y, width, height], role)) in
+        rects.iter_mut().zip(&shapes)
+    {
+        *transform = Transform::from_xyz(*x, *y, 0.).with_scale(Vec3::new(*width, *height, 1.));
+        themed.set_if_neq(*role);
+    }
+
+    let existing: Vec<Entity> = balls.iter().map(|(entity, _)| entity).collect();
+    resize_pool(&mut commands, &existing, frame.balls.len(), |commands| {
+        commands.spawn((
+            Mesh2d(view.ball.clone()),
+            MeshMaterial2d(materials.add(ColorMaterial::default())),
+            Transform::default(),
+            Themed::Ball,
+            ViewBall,
+            SpectatorView,
+        ));
+    });
+    for ((_, mut transform), [x, y]) in balls.iter_mut().zip(&frame.balls) {
+        transform.translation = Vec3::new(*x, *y, 0.);
+    }
+
+    let top = frame.height / 2.;
+    for (mut text, mut transform) in &mut score {
+        text.0.clone_from(&frame.score);
+        transform.translation = (top - 50.) * Vec3::Y;
+    }
+    for (mut text, mut transform) in &mut clock {
+        text.0.clone_from(&frame.clock);
+        transform.translation = (top - 115.) * Vec3::Y;
+    }
+}
+
+/// Steers the joined paddle with player 1's keys, whichever side it is on.
+fn steer_joined_paddle(keys: Res<ActionState<GameAction>>, mut feed: ResMut<SpectatorFeed>) {
+    if feed.seat().is_none() {
+        return;
+    }
+    let mut direction = 0.;
+    if keys.pressed(&GameAction::Player1Up) {
+        direction += 1.;
+    }
+    if keys.pressed(&GameAction::Player1Down) {
+        direction -= 1.;
+    }
+    feed.steer(direction);
+}
+
+/// Shows the game streamed to the [`SpectatorFeed`] resource for as long as it exists,
+/// and steers the side it joined.
+pub struct SpectatorViewerPlugin;
+
+impl Plugin for SpectatorViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_view_meshes).add_systems(
+            Update,
+            (
+                despawn_view.run_if(resource_removed::<SpectatorFeed>),
+                spawn_view.run_if(resource_added::<SpectatorFeed>),
+                draw_frame.run_if(resource_exists::<SpectatorFeed>),
+                steer_joined_paddle.run_if(
+                    resource_exists::<SpectatorFeed>
+                        .and(resource_exists::<ActionState<GameAction>>),
+                ),
+            )
+                .chain(),
+        );
+    }
+}
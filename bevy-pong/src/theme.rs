@@ -0,0 +1,291 @@
+//! Colors and text sizes of the arena and menus.
+//!
+//! Themes are JSON files in `assets/themes`. The classic, neon and high contrast
+//! themes are built in as well, so the game looks right without its assets; a file
+//! with the same name replaces the built-in theme, and any other file adds a new one.
+
+use bevy::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fs;
+use std::path::Path;
+
+use crate::accessibility::AccessibilitySettings;
+use crate::core::storage;
+use crate::pong::ScoreField;
+
+const STORAGE_NAME: &str = "theme";
+const THEME_DIR: &str = "assets/themes";
+
+const BUNDLED: [&str; 3] = [
+    include_str!("../assets/themes/classic.json"),
+    include_str!("../assets/themes/neon.json"),
+    include_str!("../assets/themes/high_contrast.json"),
+];
+
+/// A color written as a hex string such as `"#66B2FF"` in theme files.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThemeColor(pub Color);
+
+impl Serialize for ThemeColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_srgba().to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Srgba::hex(&hex)
+            .map(|color| ThemeColor(color.into()))
+            .map_err(|error| de::Error::custom(format!("{}: {}", hex, error)))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MenuTheme {
+    pub text: ThemeColor,
+    pub background: ThemeColor,
+    pub hover: ThemeColor,
+    pub active: ThemeColor,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ThemeFonts {
+    /// Font file under `assets` for the arena text, or the default font when unset.
+    #[serde(default)]
+    pub path: Option<String>,
+    pub score_size: f32,
+    pub clock_size: f32,
+    pub heading_size: f32,
+    pub text_size: f32,
+}
+
+/// The look of the game, applied to everything marked [`Themed`] and to the menus.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub background: ThemeColor,
+    pub walls: ThemeColor,
+    pub left_paddle: ThemeColor,
+    pub right_paddle: ThemeColor,
+    pub ball: ThemeColor,
+    pub text: ThemeColor,
+    pub menu: MenuTheme,
+    pub fonts: ThemeFonts,
+}
+
+impl Theme {
+    pub fn paddle(&self, side: ScoreField) -> Color {
+        match side {
+            ScoreField::Left => self.left_paddle.0,
+            ScoreField::Right => self.right_paddle.0,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        serde_json::from_str(BUNDLED[0]).expect("the classic theme is valid")
+    }
+}
+
+/// Every theme available to pick from.
+#[derive(Resource, Clone, Debug)]
+pub struct ThemeLibrary {
+    themes: Vec<Theme>,
+}
+
+impl ThemeLibrary {
+    /// The built-in themes, then every theme file in `dir`.
+    pub fn load_from(dir: &Path) -> Self {
+        let mut library = Self {
+            themes: BUNDLED
+                .iter()
+                .map(|contents| serde_json::from_str(contents).expect("built-in themes are valid"))
+                .collect(),
+        };
+
+        let Ok(entries) = fs::read_dir(dir) else {
+            return library;
+        };
+        let mut paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let theme = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|contents| {
+                    serde_json::from_str::<Theme>(&contents).map_err(|error| error.to_string())
+                });
+            match theme {
+                Ok(theme) => library.add(theme),
+                Err(error) => warn!("Ignoring theme {}: {}", path.display(), error),
+            }
+        }
+        library
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(Path::new(THEME_DIR))
+    }
+
+    fn add(&mut self, theme: Theme) {
+        match self
+            .themes
+            .iter_mut()
+            .find(|known| known.name == theme.name)
+        {
+            Some(known) => *known = theme,
+            None => self.themes.push(theme),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.themes.iter().map(|theme| theme.name.as_str())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.iter().find(|theme| theme.name == name)
+    }
+}
+
+/// Name of the chosen theme, saved to `theme.json` in the data directory.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub name: String,
+}
+
+impl ThemeSettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            name: "classic".into(),
+        }
+    }
+}
+
+/// What part of the theme an entity takes its color, and for text its size, from.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Themed {
+    Walls,
+    Paddle(ScoreField),
+    Ball,
+    Score,
+    Clock,
+}
+
+impl Themed {
+    fn color(&self, theme: &Theme) -> Color {
+        match self {
+            Themed::Walls => theme.walls.0,
+            Themed::Paddle(side) => theme.paddle(*side),
+            Themed::Ball => theme.ball.0,
+            Themed::Score | Themed::Clock => theme.text.0,
+        }
+    }
+
+    fn font_size(&self, theme: &Theme) -> f32 {
+        match self {
+            Themed::Clock => theme.fonts.clock_size,
+            _ => theme.fonts.score_size,
+        }
+    }
+}
+
+fn select_theme(
+    settings: Res<ThemeSettings>,
+    accessibility: Option<Res<AccessibilitySettings>>,
+    library: Res<ThemeLibrary>,
+    mut theme: ResMut<Theme>,
+) {
+    let accessibility_changed = accessibility.as_ref().is_some_and(|a| a.is_changed());
+    if !settings.is_changed() && !accessibility_changed {
+        return;
+    }
+
+    let name = accessibility
+        .as_ref()
+        .map_or(settings.name.as_str(), |a| a.theme_name(&settings.name));
+    let Some(selected) = library.get(name) else {
+        warn!("Unknown theme '{}'", name);
+        return;
+    };
+    let mut selected = selected.clone();
+    if let Some(accessibility) = accessibility {
+        accessibility.adapt(&mut selected);
+    }
+    theme.set_if_neq(selected);
+}
+
+impl storage::Persistent for ThemeSettings {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
+
+/// Recolors themed entities when they appear, change role, or the theme changes.
+fn paint_themed(
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+    mut clear_color: ResMut<ClearColor>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    shapes: Query<(Ref<Themed>, &MeshMaterial2d<ColorMaterial>)>,
+    mut texts: Query<(Ref<Themed>, &mut TextColor, &mut TextFont)>,
+) {
+    let repaint_all = theme.is_changed();
+    if repaint_all {
+        clear_color.0 = theme.background.0;
+    }
+
+    for (themed, material) in &shapes {
+        if repaint_all || themed.is_changed() {
+            if let Some(material) = materials.get_mut(&material.0) {
+                material.color = themed.color(&theme);
+            }
+        }
+    }
+
+    for (themed, mut color, mut font) in &mut texts {
+        if repaint_all || themed.is_changed() {
+            color.0 = themed.color(&theme);
+            font.font_size = themed.font_size(&theme);
+            font.font = theme
+                .fonts
+                .path
+                .as_ref()
+                .map(|path| asset_server.load(path))
+                .unwrap_or_default();
+        }
+    }
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        let library = ThemeLibrary::load();
+        let settings = ThemeSettings::load();
+        let theme = library.get(&settings.name).cloned().unwrap_or_default();
+
+        app.insert_resource(ClearColor(theme.background.0))
+            .insert_resource(theme)
+            .insert_resource(library)
+            .insert_resource(settings)
+            .add_systems(
+                Update,
+                (select_theme, storage::save_on_change::<ThemeSettings>),
+            )
+            .add_systems(PostUpdate, paint_themed.before(bevy::text::Update2dText));
+    }
+}
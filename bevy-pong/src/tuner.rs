@@ -0,0 +1,240 @@
+//! Calibrates the difficulty presets with headless self-play.
+//!
+//! For each named difficulty, a small evolutionary search looks for an [`AiProfile`]
+//! whose win rate against [`REFERENCE`] matches that difficulty's target. Every
+//! candidate plays the same seeded matches, half of them on each side, so
+//! results are reproducible and candidates are compared on equal terms.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+use crate::core::settings::{AiPresets, AiProfile, Difficulty, GameSettings, PlayerType};
+use crate::env::{Action, PongEnv};
+use crate::pong::constants::TICK_RATE;
+
+/// Opponent every candidate is measured against.
+pub const REFERENCE: AiProfile = AiProfile {
+    speed: 10.,
+    reaction_ticks: 0,
+    prediction_noise: 35.,
+    aggression: 0.,
+};
+
+/// Win rates against [`REFERENCE`] the easy, difficult and impossible presets aim for.
+pub const TARGET_WIN_RATES: [f32; 3] = [0.2, 0.5, 0.9];
+
+/// Longest a match may run before it counts as a draw.
+const MAX_MATCH_SECONDS: f64 = 180.;
+
+pub struct TunerConfig {
+    pub generations: u32,
+    pub population: usize,
+    /// Matches each candidate plays per evaluation.
+    pub matches: u32,
+    pub seed: u64,
+}
+
+impl Default for TunerConfig {
+    fn default() -> Self {
+        Self {
+            generations: 10,
+            population: 8,
+            matches: 20,
+            seed: 0,
+        }
+    }
+}
+
+/// How a tuning run is getting on, for whoever started it to show.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TuningProgress {
+    Started {
+        name: &'static str,
+        target: f32,
+    },
+    /// Best candidate after a generation.
+    Generation {
+        generation: u32,
+        profile: AiProfile,
+        win_rate: f32,
+        target: f32,
+    },
+    Fitted {
+        name: &'static str,
+        profile: AiProfile,
+        win_rate: f32,
+    },
+}
+
+impl fmt::Display for TuningProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuningProgress::Started { name, target } => {
+                write!(
+                    f,
+                    "Tuning {} towards a {:.0}% win rate",
+                    name,
+                    target * 100.
+                )
+            }
+            TuningProgress::Generation {
+                generation,
+                profile,
+                win_rate,
+                target,
+            } => write!(
+                f,
+                "  generation {}: win rate {:.2} (target {:.2}) with {:?}",
+                generation, win_rate, target, profile
+            ),
+            TuningProgress::Fitted {
+                name,
+                profile,
+                win_rate,
+            } => write!(
+                f,
+                "  {} wins {:.0}% with {:?}",
+                name,
+                win_rate * 100.,
+                profile
+            ),
+        }
+    }
+}
+
+fn mutate(profile: &AiProfile, rng: &mut ChaCha8Rng) -> AiProfile {
+    AiProfile {
+        speed: (profile.speed + rng.gen_range(-1.5..=1.5)).clamp(2., 20.),
+        reaction_ticks: profile
+            .reaction_ticks
+            .saturating_add_signed(rng.gen_range(-3..=3))
+            .min(30),
+        prediction_noise: (profile.prediction_noise + rng.gen_range(-15. ..=15.)).clamp(0., 120.),
+        aggression: (profile.aggression + rng.gen_range(-0.2..=0.2)).clamp(0., 1.),
+    }
+}
+
+/// Plays `config.matches` matches of `candidate` against `reference` and returns
+/// the candidate's share of wins, counting draws as half.
+pub fn win_rate(
+    env: &mut PongEnv,
+    candidate: AiProfile,
+    reference: AiProfile,
+    config: &TunerConfig,
+) -> f32 {
+    let max_ticks = (MAX_MATCH_SECONDS * TICK_RATE) as u32;
+    let mut wins = 0.;
+
+    for game in 0..config.matches {
+        // Alternate sides so neither profile gains from serving patterns.
+        let candidate_left = game % 2 == 0;
+        let (left, right) = if candidate_left {
+            (candidate, reference)
+        } else {
+            (reference, candidate)
+        };
+        let mut settings = env.app_mut().world_mut().resource_mut::<GameSettings>();
+        settings.update_players(1, PlayerType::Computer(Difficulty::Custom(left)));
+        settings.update_players(2, PlayerType::Computer(Difficulty::Custom(right)));
+
+        let mut observation = env.reset(config.seed.wrapping_add(game as u64));
+        let mut done = false;
+        for _ in 0..max_ticks {
+            (observation, _, done) = env.step(Action::Stay, Action::Stay);
+            if done {
+                break;
+            }
+        }
+
+        let (own, other) = if candidate_left {
+            (observation.score1, observation.score2)
+        } else {
+            (observation.score2, observation.score1)
+        };
+        wins += if !done || own == other {
+            0.5
+        } else if own > other {
+            1.
+        } else {
+            0.
+        };
+    }
+
+    wins / config.matches as f32
+}
+
+/// Searches for a profile winning `target` of its matches against [`REFERENCE`],
+/// starting from `start`.
+pub fn fit(
+    env: &mut PongEnv,
+    start: AiProfile,
+    target: f32,
+    config: &TunerConfig,
+    rng: &mut ChaCha8Rng,
+    progress: &mut impl FnMut(TuningProgress),
+) -> (AiProfile, f32) {
+    let population = config.population.max(2);
+    let mut candidates: Vec<(AiProfile, f32)> = (0..population)
+        .map(|i| if i == 0 { start } else { mutate(&start, rng) })
+        .map(|profile| (profile, win_rate(env, profile, REFERENCE, config)))
+        .collect();
+    let error = |rate: f32| (rate - target).abs();
+
+    for generation in 0..config.generations {
+        candidates.sort_by(|a, b| error(a.1).total_cmp(&error(b.1)));
+        let (best, rate) = candidates[0];
+        progress(TuningProgress::Generation {
+            generation: generation + 1,
+            profile: best,
+            win_rate: rate,
+            target,
+        });
+        if error(rate) <= 0.5 / config.matches as f32 {
+            break;
+        }
+
+        // Keep the better half and refill with mutations of it.
+        candidates.truncate(population / 2);
+        while candidates.len() < population {
+            let parent = candidates[rng.gen_range(0..population / 2)].0;
+            let child = mutate(&parent, rng);
+            candidates.push((child, win_rate(env, child, REFERENCE, config)));
+        }
+    }
+
+    candidates.sort_by(|a, b| error(a.1).total_cmp(&error(b.1)));
+    candidates[0]
+}
+
+/// Fits every preset, starting from `presets`, in matches played with `settings`.
+pub fn tune(
+    settings: GameSettings,
+    presets: AiPresets,
+    config: &TunerConfig,
+    mut progress: impl FnMut(TuningProgress),
+) -> AiPresets {
+    let mut env = PongEnv::new(settings);
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let mut tuned = presets;
+
+    for ((name, preset), target) in [
+        ("easy", &mut tuned.easy),
+        ("difficult", &mut tuned.difficult),
+        ("impossible", &mut tuned.impossible),
+    ]
+    .into_iter()
+    .zip(TARGET_WIN_RATES)
+    {
+        progress(TuningProgress::Started { name, target });
+        let (profile, win_rate) = fit(&mut env, *preset, target, config, &mut rng, &mut progress);
+        progress(TuningProgress::Fitted {
+            name,
+            profile,
+            win_rate,
+        });
+        *preset = profile;
+    }
+
+    tuned
+}
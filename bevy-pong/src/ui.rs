@@ -1,4 +1,4 @@
 mod menu;
 mod systems;
 
-pub use systems::MenuSystemsPlugin;
+pub use systems::{ignore_widget_clicks, MenuSystemsPlugin};
@@ -3,16 +3,45 @@ pub mod builder;
 pub mod components;
 
 pub mod style {
-    use bevy_egui::egui::Color32;
+    use bevy_egui::egui::{self, Color32};
 
     pub const BUTTON_WIDTH: f32 = 200.0;
     pub const BUTTON_HEIGHT: f32 = 50.0;
     pub const SPACING: f32 = 10.0;
-    pub const HEADING_SIZE: f32 = 48.0;
-    pub const TEXT_SIZE: f32 = 24.0;
 
-    pub const PRIMARY_COLOR: Color32 = Color32::WHITE;
-    pub const BACKGROUND_COLOR: Color32 = Color32::from_rgb(0, 0, 0);
-    pub const HOVER_COLOR: Color32 = Color32::from_rgb(102, 178, 255);
-    pub const ACTIVE_COLOR: Color32 = Color32::from_rgb(128, 128, 128);
+    /// Text sizes and colors of the menus, kept in the egui context so every
+    /// component can read the current theme.
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub struct MenuStyle {
+        pub heading_size: f32,
+        pub text_size: f32,
+        pub text: Color32,
+        pub background: Color32,
+        pub hover: Color32,
+        pub active: Color32,
+    }
+
+    impl Default for MenuStyle {
+        fn default() -> Self {
+            Self {
+                heading_size: 48.0,
+                text_size: 24.0,
+                text: Color32::WHITE,
+                background: Color32::from_rgb(0, 0, 0),
+                hover: Color32::from_rgb(102, 178, 255),
+                active: Color32::from_rgb(128, 128, 128),
+            }
+        }
+    }
+
+    impl MenuStyle {
+        pub fn get(ctx: &egui::Context) -> Self {
+            ctx.data(|data| data.get_temp(egui::Id::NULL))
+                .unwrap_or_default()
+        }
+
+        pub fn set(self, ctx: &egui::Context) {
+            ctx.data_mut(|data| data.insert_temp(egui::Id::NULL, self));
+        }
+    }
 }
@@ -1,10 +1,7 @@
 use crate::ui::menu::components::MenuComponent;
-use crate::ui::menu::style;
+use crate::ui::menu::style::{self, MenuStyle};
 use bevy::prelude::*;
-use bevy_egui::{
-    egui::{self, Color32},
-    EguiContexts,
-};
+use bevy_egui::{egui, EguiContexts};
 
 pub struct MenuBuilder {
     heading: String,
@@ -39,14 +36,15 @@ impl MenuBuilder {
     }
 
     fn render_menu(&mut self, ctx: &egui::Context, commands: &mut Commands) {
+        let menu_style = MenuStyle::get(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.add_space(self.top_spacing);
 
                 ui.heading(
                     egui::RichText::new(&self.heading)
-                        .size(style::HEADING_SIZE)
-                        .color(style::PRIMARY_COLOR)
+                        .size(menu_style.heading_size)
+                        .color(menu_style.text)
                         .strong(),
                 );
                 ui.add_space(40.);
@@ -60,21 +58,23 @@ impl MenuBuilder {
     }
 
     fn setup_style(&self, ctx: &mut egui::Context) {
+        let menu_style = MenuStyle::get(ctx);
         ctx.style_mut(|style| {
-            style.visuals.widgets.inactive = self.create_widget_style(style::BACKGROUND_COLOR);
-            style.visuals.widgets.active = self.create_widget_style(style::ACTIVE_COLOR);
-            style.visuals.widgets.hovered = self.create_widget_style(style::HOVER_COLOR);
+            style.visuals.panel_fill = menu_style.background;
+            style.visuals.widgets.inactive = self.create_widget_style(menu_style.background);
+            style.visuals.widgets.active = self.create_widget_style(menu_style.active);
+            style.visuals.widgets.hovered = self.create_widget_style(menu_style.hover);
         });
     }
 
     fn create_widget_style(&self, color: egui::Color32) -> egui::style::WidgetVisuals {
         egui::style::WidgetVisuals {
-            bg_fill: color.clone(),
+            bg_fill: color,
             fg_stroke: egui::Stroke::NONE,
             bg_stroke: egui::Stroke::NONE,
             rounding: egui::Rounding::default(),
             expansion: 0.,
-            weak_bg_fill: color.clone(),
+            weak_bg_fill: color,
         }
     }
 }
@@ -1,12 +1,33 @@
+use crate::audio::{MenuFocused, PlaySfx, Sfx};
 use crate::ui::menu::actions::MenuAction;
-use crate::ui::menu::style;
+use crate::ui::menu::style::{self, MenuStyle};
 use bevy::prelude::*;
 use bevy_egui::egui;
+use std::ops::RangeInclusive;
 
 pub trait MenuComponent {
     fn build(&mut self, ui: &mut egui::Ui, commands: &mut Commands);
 }
 
+/// Triggers [`MenuFocused`] when `response` gains keyboard focus or the pointer
+/// moves onto it.
+fn announce_focus(response: &egui::Response, commands: &mut Commands) {
+    let ctx = &response.ctx;
+    let frame = ctx.cumulative_pass_nr();
+    let id = egui::Id::new("menu_hovered");
+
+    let hovered_before = ctx.data(|data| data.get_temp::<(egui::Id, u64)>(id));
+    let entered = response.hovered()
+        && !hovered_before.is_some_and(|(widget, seen)| widget == response.id && seen + 1 >= frame);
+    if response.hovered() {
+        ctx.data_mut(|data| data.insert_temp(id, (response.id, frame)));
+    }
+
+    if entered || response.gained_focus() {
+        commands.trigger(MenuFocused);
+    }
+}
+
 pub struct MenuLabel {
     label: String,
 }
@@ -25,8 +46,8 @@ impl MenuComponent for MenuLabel {
             egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
             egui::Label::new(
                 egui::RichText::new(&self.label)
-                    .color(style::PRIMARY_COLOR)
-                    .size(style::TEXT_SIZE),
+                    .color(MenuStyle::get(ui.ctx()).text)
+                    .size(MenuStyle::get(ui.ctx()).text_size),
             ),
         );
     }
@@ -54,18 +75,18 @@ impl MenuSelectableLabel {
 
 impl MenuComponent for MenuSelectableLabel {
     fn build(&mut self, ui: &mut egui::Ui, commands: &mut Commands) {
-        if ui
-            .add_sized(
-                egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
-                egui::SelectableLabel::new(
-                    self.selected,
-                    egui::RichText::new(&self.label)
-                        .size(style::TEXT_SIZE)
-                        .color(style::PRIMARY_COLOR),
-                ),
-            )
-            .clicked()
-        {
+        let response = ui.add_sized(
+            egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
+            egui::SelectableLabel::new(
+                self.selected,
+                egui::RichText::new(&self.label)
+                    .size(MenuStyle::get(ui.ctx()).text_size)
+                    .color(MenuStyle::get(ui.ctx()).text),
+            ),
+        );
+        announce_focus(&response, commands);
+        if response.clicked() {
+            commands.trigger(PlaySfx::new(Sfx::MenuSelect));
             self.action.execute(commands);
         }
     }
@@ -87,22 +108,68 @@ impl MenuButton {
 
 impl MenuComponent for MenuButton {
     fn build(&mut self, ui: &mut egui::Ui, commands: &mut Commands) {
-        if ui
-            .add_sized(
-                egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
-                egui::Button::new(
-                    egui::RichText::new(&self.label)
-                        .size(style::TEXT_SIZE)
-                        .color(style::PRIMARY_COLOR),
-                ),
-            )
-            .clicked()
-        {
+        let response = ui.add_sized(
+            egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
+            egui::Button::new(
+                egui::RichText::new(&self.label)
+                    .size(MenuStyle::get(ui.ctx()).text_size)
+                    .color(MenuStyle::get(ui.ctx()).text),
+            ),
+        );
+        announce_focus(&response, commands);
+        if response.clicked() {
+            commands.trigger(PlaySfx::new(Sfx::MenuSelect));
             self.action.execute(commands);
         }
     }
 }
 
+type SliderCallback = Box<dyn Fn(&mut Commands, f32)>;
+
+pub struct MenuSlider {
+    label: String,
+    value: f32,
+    range: RangeInclusive<f32>,
+    on_change: SliderCallback,
+}
+
+impl MenuSlider {
+    pub fn new(
+        label: impl Into<String>,
+        value: f32,
+        range: RangeInclusive<f32>,
+        on_change: impl Fn(&mut Commands, f32) + 'static,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            range,
+            on_change: Box::new(on_change),
+        }
+    }
+}
+
+impl MenuComponent for MenuSlider {
+    fn build(&mut self, ui: &mut egui::Ui, commands: &mut Commands) {
+        ui.add_sized(
+            egui::Vec2::new(style::BUTTON_WIDTH, style::BUTTON_HEIGHT),
+            egui::Label::new(
+                egui::RichText::new(&self.label)
+                    .color(MenuStyle::get(ui.ctx()).text)
+                    .size(MenuStyle::get(ui.ctx()).text_size),
+            ),
+        );
+
+        ui.spacing_mut().slider_width = style::BUTTON_WIDTH;
+        let response =
+            ui.add(egui::Slider::new(&mut self.value, self.range.clone()).show_value(false));
+        announce_focus(&response, commands);
+        if response.changed() {
+            (self.on_change)(commands, self.value);
+        }
+    }
+}
+
 pub struct MenuLayoutHorizontal {
     components: Vec<Box<dyn MenuComponent>>,
 }
@@ -140,3 +207,33 @@ impl MenuComponent for MenuLayoutHorizontal {
         );
     }
 }
+
+pub struct MenuLayoutVertical {
+    components: Vec<Box<dyn MenuComponent>>,
+}
+
+impl MenuLayoutVertical {
+    pub fn new() -> Self {
+        Self {
+            components: Vec::new(),
+        }
+    }
+
+    pub fn add_component(mut self, component: impl MenuComponent + 'static) -> Self {
+        self.components.push(Box::new(component));
+        self
+    }
+}
+
+impl MenuComponent for MenuLayoutVertical {
+    fn build(&mut self, ui: &mut egui::Ui, commands: &mut Commands) {
+        ui.vertical_centered(|ui| {
+            for (index, component) in self.components.iter_mut().enumerate() {
+                if index > 0 {
+                    ui.add_space(style::SPACING);
+                }
+                component.build(ui, commands);
+            }
+        });
+    }
+}
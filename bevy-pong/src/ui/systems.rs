@@ -1,22 +1,56 @@
+use bevy::input::gamepad::GamepadConnectionEvent;
 use bevy::prelude::*;
-use bevy_egui::{EguiContexts, EguiPlugin};
+use bevy::window::WindowFocused;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use leafwing_input_manager::prelude::*;
+use std::time::Duration;
 
+use crate::accessibility::{
+    AccessibilitySettings, InputMode, SidePalette, GAME_SPEEDS, TEXT_SCALES,
+};
+use crate::achievements::{AchievementDefinitions, AchievementProgress, AchievementUnlocked};
+use crate::audio::AudioSettings;
+use crate::bot::BotSettings;
 use crate::core::{
-    commands::UpdatePlayerCommand,
-    controls::{listen_for_keys, ControlRemapping, GameAction},
-    settings::{Difficulty, GameSettings, PlayerType},
+    commands::{
+        ForfeitCommand, ResolveConflictCommand, UpdateModeCommand, UpdateMovementCommand,
+        UpdatePlayerCommand, UpdateTimeLimitCommand,
+    },
+    controls::{
+        bindings, listen_for_keys, uses_gamepad, ControlRemapping, ControlScheme, ControlSchemes,
+        GameAction, BINDING_SLOTS, CANCEL_KEY,
+    },
+    high_scores::HighScores,
+    settings::{
+        Difficulty, GameMode, GameSettings, PaddleMovement, PlayerType, PracticeOpponent,
+        PracticeSettings, SurvivalSettings,
+    },
     states::{
-        ControlsSet, EndgameSet, GameState, MainSet, PausedSet, PausedState, PlayingSet,
-        StartGameSet,
+        AccessibilitySet, AchievementsSet, ControlsSet, EndgameSet, GameState, JoinLanSet,
+        LeaderboardsSet, MainSet, PauseChoice, PauseMenu, PausedSet, PausedState, PlayingSet,
+        PracticeSet, SettingsSet, SpectatingSet, StartGameSet, SurvivalSet,
     },
+    storage,
 };
-use crate::pong::Score;
+use crate::effects::EffectsSettings;
+use crate::lan::LanBrowser;
+use crate::leaderboard::{Leaderboard, LeaderboardStandings, MODES};
+use crate::pong::{RestartMatch, Score, ScoreField, SurvivalRun};
+use crate::spectator::{SpectatorFeed, SpectatorHost, DEFAULT_DELAY_MS};
+use crate::theme::{Theme, ThemeLibrary, ThemeSettings};
 use crate::ui::menu::{
-    actions::{ChangeStateMenuAction, CommandMenuAction, QuitMenuAction, UpdateResourceMenuAction},
+    actions::{
+        ChangeStateMenuAction, ClosureMenuAction, CommandMenuAction, QuitMenuAction,
+        UpdateResourceMenuAction,
+    },
     builder::MenuBuilder,
-    components::{MenuButton, MenuLabel, MenuLayoutHorizontal, MenuSelectableLabel},
+    components::{
+        MenuButton, MenuLabel, MenuLayoutHorizontal, MenuLayoutVertical, MenuSelectableLabel,
+        MenuSlider,
+    },
+    style::MenuStyle,
 };
+use crate::windows::window::DisplaySettings;
 
 fn main_menu(mut commands: Commands, contexts: EguiContexts) {
     let builder = MenuBuilder::new("");
@@ -25,10 +59,38 @@ fn main_menu(mut commands: Commands, contexts: EguiContexts) {
             "Start Game",
             ChangeStateMenuAction::new(GameState::Startgame),
         ))
+        .add_component(MenuButton::new(
+            "Practice",
+            ChangeStateMenuAction::new(GameState::Practice),
+        ))
+        .add_component(MenuButton::new(
+            "Survival",
+            ChangeStateMenuAction::new(GameState::Survival),
+        ))
+        .add_component(MenuButton::new(
+            "Join LAN Game",
+            ChangeStateMenuAction::new(GameState::JoinLan),
+        ))
+        .add_component(MenuButton::new(
+            "Leaderboards",
+            ChangeStateMenuAction::new(GameState::Leaderboards),
+        ))
+        .add_component(MenuButton::new(
+            "Achievements",
+            ChangeStateMenuAction::new(GameState::Achievements),
+        ))
         .add_component(MenuButton::new(
             "Controls",
             ChangeStateMenuAction::new(GameState::Controls),
         ))
+        .add_component(MenuButton::new(
+            "Settings",
+            ChangeStateMenuAction::new(GameState::Settings),
+        ))
+        .add_component(MenuButton::new(
+            "Accessibility",
+            ChangeStateMenuAction::new(GameState::Accessibility),
+        ))
         .add_component(MenuButton::new("Quit Game", QuitMenuAction))
         .build(contexts, &mut commands);
 }
@@ -41,62 +103,436 @@ fn destroy_controls_menu(mut commands: Commands) {
     commands.remove_resource::<ControlRemapping>();
 }
 
-fn controls_menu(mut commands: Commands, contexts: EguiContexts, keys: Res<InputMap<GameAction>>) {
-    let mut builder = MenuBuilder::new("Controls")
-        .with_top_spacing(25.)
-        .add_component(MenuLabel::new("Player 1:"));
+fn controls_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    keys: Res<InputMap<GameAction>>,
+    remapping: Res<ControlRemapping>,
+    schemes: Res<ControlSchemes>,
+) {
+    controls_components(&keys, &remapping, *schemes)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+/// Clears mouse presses that land on a menu widget, such as Back, so they aren't
+/// bound while listening for an input. Presses on the menu's background are kept.
+pub fn ignore_widget_clicks(ctx: &egui::Context, mouse: &mut ButtonInput<MouseButton>) {
+    let on_widget = ctx.is_using_pointer()
+        || ctx.viewport(|viewport| viewport.hits.click.is_some() || viewport.hits.drag.is_some());
+    if !on_widget {
+        return;
+    }
+
+    let pressed: Vec<MouseButton> = mouse.get_just_pressed().copied().collect();
+    for button in pressed {
+        mouse.clear_just_pressed(button);
+    }
+}
+
+fn ignore_menu_clicks(mut contexts: EguiContexts, mut mouse: ResMut<ButtonInput<MouseButton>>) {
+    ignore_widget_clicks(contexts.ctx_mut(), &mut mouse);
+}
 
-    builder = control_selection_button(&keys, builder, GameAction::Player1Up);
-    builder = control_selection_button(&keys, builder, GameAction::Player1Down);
+fn controls_components(
+    keys: &Res<InputMap<GameAction>>,
+    remapping: &Res<ControlRemapping>,
+    schemes: ControlSchemes,
+) -> MenuBuilder {
+    let mut builder = MenuBuilder::new("Controls").with_top_spacing(25.);
+
+    if let Some(conflict) = remapping.conflict() {
+        return builder
+            .add_component(MenuLabel::new(format!(
+                "{} is already bound to {:?}",
+                conflict.binding, conflict.action
+            )))
+            .add_component(
+                MenuLayoutHorizontal::new()
+                    .add_component(MenuButton::new(
+                        "Swap",
+                        CommandMenuAction::new(ResolveConflictCommand::new(true)),
+                    ))
+                    .add_component(MenuButton::new(
+                        "Cancel",
+                        CommandMenuAction::new(ResolveConflictCommand::new(false)),
+                    )),
+            );
+    }
+
+    builder = builder.add_component(MenuLabel::new("Player 1:"));
+    builder = builder.add_component(control_scheme_selection(schemes.player1, |scheme| {
+        ControlSchemes {
+            player1: scheme,
+            ..schemes
+        }
+    }));
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player1Up);
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player1Down);
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player1Dash);
     builder = builder.add_component(MenuLabel::new("Player 2:"));
-    builder = control_selection_button(&keys, builder, GameAction::Player2Up);
-    builder = control_selection_button(&keys, builder, GameAction::Player2Down);
+    builder = builder.add_component(control_scheme_selection(schemes.player2, |scheme| {
+        ControlSchemes {
+            player2: scheme,
+            ..schemes
+        }
+    }));
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player2Up);
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player2Down);
+    builder = control_selection_button(keys, remapping, builder, GameAction::Player2Dash);
     builder = builder.add_component(MenuLabel::new(""));
 
-    builder = control_selection_button(&keys, builder, GameAction::Menu);
+    control_selection_button(keys, remapping, builder, GameAction::Menu).add_component(
+        MenuButton::new(
+            "Reset to Defaults",
+            ClosureMenuAction::new(|commands: &mut Commands| {
+                commands.insert_resource(GameAction::default_input_map());
+                commands.insert_resource(ControlRemapping::default());
+            }),
+        ),
+    )
+}
 
-    builder
-        .add_component(MenuButton::new(
-            "Back",
-            ChangeStateMenuAction::new(GameState::Main),
+fn control_scheme_selection(
+    current: ControlScheme,
+    with_scheme: impl Fn(ControlScheme) -> ControlSchemes,
+) -> MenuLayoutHorizontal {
+    MenuLayoutHorizontal::new()
+        .add_component(MenuSelectableLabel::new(
+            "Keyboard",
+            current == ControlScheme::Keyboard,
+            UpdateResourceMenuAction::new(with_scheme(ControlScheme::Keyboard)),
+        ))
+        .add_component(MenuSelectableLabel::new(
+            "Mouse / Touch",
+            current == ControlScheme::Pointer,
+            UpdateResourceMenuAction::new(with_scheme(ControlScheme::Pointer)),
         ))
-        .build(contexts, &mut commands);
 }
 
 fn control_selection_button(
     keys: &Res<InputMap<GameAction>>,
+    remapping: &Res<ControlRemapping>,
     builder: MenuBuilder,
     control: GameAction,
 ) -> MenuBuilder {
-    let current_keys = keys
-        .get(&control)
-        .map(|key_set| {
-            key_set
-                .iter()
-                .filter_map(|key| match key {
-                    UserInputWrapper::Button(button) => Some(format!("{:?}", button)),
-                    _ => None,
+    let current_keys = bindings(keys, control);
+    let mut row =
+        MenuLayoutHorizontal::new().add_component(MenuLabel::new(format!("{:?}", control)));
+
+    for slot in 0..BINDING_SLOTS {
+        let text = match remapping.listening_for(control, slot) {
+            Some(remaining) => format!(
+                "Press input ({:?} cancels, {:.0}s)",
+                CANCEL_KEY,
+                remaining.ceil()
+            ),
+            None => current_keys
+                .get(slot)
+                .map(|binding| binding.to_string())
+                .unwrap_or_else(|| "[Not Set]".to_string()),
+        };
+
+        row = row.add_component(MenuButton::new(
+            text,
+            UpdateResourceMenuAction::new(ControlRemapping::start_remapping(control, slot)),
+        ));
+    }
+
+    builder.add_component(row)
+}
+
+fn settings_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    audio: Res<AudioSettings>,
+    display: Res<DisplaySettings>,
+    themes: Res<ThemeLibrary>,
+    theme: Res<ThemeSettings>,
+) {
+    settings_components(*audio, *display, &themes, &theme)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn settings_components(
+    audio: AudioSettings,
+    display: DisplaySettings,
+    themes: &ThemeLibrary,
+    theme: &ThemeSettings,
+) -> MenuBuilder {
+    MenuBuilder::new("Settings")
+        .with_top_spacing(25.)
+        .add_component(MenuSlider::new(
+            "Master Volume",
+            audio.master,
+            0.0..=1.0,
+            move |commands, master| commands.insert_resource(AudioSettings { master, ..audio }),
+        ))
+        .add_component(MenuSlider::new(
+            "Effects Volume",
+            audio.sfx,
+            0.0..=1.0,
+            move |commands, sfx| commands.insert_resource(AudioSettings { sfx, ..audio }),
+        ))
+        .add_component(MenuSlider::new(
+            "Music Volume",
+            audio.music,
+            0.0..=1.0,
+            move |commands, music| commands.insert_resource(AudioSettings { music, ..audio }),
+        ))
+        .add_component(MenuButton::new(
+            "Reset Audio",
+            ClosureMenuAction::new(|commands: &mut Commands| {
+                commands.insert_resource(AudioSettings::default())
+            }),
+        ))
+        .add_component(
+            MenuLayoutHorizontal::new()
+                .add_component(MenuSelectableLabel::new(
+                    "Windowed",
+                    !display.fullscreen,
+                    UpdateResourceMenuAction::new(DisplaySettings {
+                        fullscreen: false,
+                        ..display
+                    }),
+                ))
+                .add_component(MenuSelectableLabel::new(
+                    "Fullscreen",
+                    display.fullscreen,
+                    UpdateResourceMenuAction::new(DisplaySettings {
+                        fullscreen: true,
+                        ..display
+                    }),
+                )),
+        )
+        .add_component(
+            MenuLayoutHorizontal::new()
+                .add_component(MenuSelectableLabel::new(
+                    "VSync On",
+                    display.vsync,
+                    UpdateResourceMenuAction::new(DisplaySettings {
+                        vsync: true,
+                        ..display
+                    }),
+                ))
+                .add_component(MenuSelectableLabel::new(
+                    "VSync Off",
+                    !display.vsync,
+                    UpdateResourceMenuAction::new(DisplaySettings {
+                        vsync: false,
+                        ..display
+                    }),
+                )),
+        )
+        .add_component(theme_selection(themes, theme))
+}
+
+/// Two labels choosing between the off and on values of a setting.
+fn toggle_row<T: Resource + Copy>(
+    off: &str,
+    on: &str,
+    enabled: bool,
+    set: impl Fn(bool) -> T,
+) -> MenuLayoutHorizontal {
+    MenuLayoutHorizontal::new()
+        .add_component(MenuSelectableLabel::new(
+            off,
+            !enabled,
+            UpdateResourceMenuAction::new(set(false)),
+        ))
+        .add_component(MenuSelectableLabel::new(
+            on,
+            enabled,
+            UpdateResourceMenuAction::new(set(true)),
+        ))
+}
+
+fn accessibility_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    settings: Res<AccessibilitySettings>,
+    effects: Res<EffectsSettings>,
+    audio: Res<AudioSettings>,
+) {
+    let settings = *settings;
+    let audio = *audio;
+    let palettes =
+        SidePalette::ALL
+            .into_iter()
+            .fold(MenuLayoutHorizontal::new(), |row, palette| {
+                row.add_component(MenuSelectableLabel::new(
+                    palette.label(),
+                    settings.palette == palette,
+                    UpdateResourceMenuAction::new(AccessibilitySettings {
+                        palette,
+                        ..settings
+                    }),
+                ))
+            });
+
+    MenuBuilder::new("Accessibility")
+        .with_top_spacing(25.)
+        .add_component(palettes)
+        .add_component(toggle_row(
+            "Normal Contrast",
+            "High Contrast",
+            settings.high_contrast,
+            |high_contrast| AccessibilitySettings {
+                high_contrast,
+                ..settings
+            },
+        ))
+        .add_component(MenuSlider::new(
+            "Text Size",
+            settings.text_scale,
+            TEXT_SCALES,
+            move |commands, text_scale| {
+                commands.insert_resource(AccessibilitySettings {
+                    text_scale,
+                    ..settings
                 })
-                .collect::<Vec<String>>()
-                .join(", ")
+            },
+        ))
+        .add_component(toggle_row(
+            "Normal Size",
+            "Large Ball & Paddles",
+            settings.large_objects,
+            |large_objects| AccessibilitySettings {
+                large_objects,
+                ..settings
+            },
+        ))
+        .add_component(MenuSlider::new(
+            "Game Speed",
+            settings.game_speed,
+            GAME_SPEEDS,
+            move |commands, game_speed| {
+                commands.insert_resource(AccessibilitySettings {
+                    game_speed,
+                    ..settings
+                })
+            },
+        ))
+        .add_component(toggle_row(
+            "Hold to Move",
+            "Toggle Movement",
+            settings.input == InputMode::Toggle,
+            |toggle| AccessibilitySettings {
+                input: if toggle {
+                    InputMode::Toggle
+                } else {
+                    InputMode::Hold
+                },
+                ..settings
+            },
+        ))
+        .add_component(toggle_row(
+            "No Flashing",
+            "Flashing",
+            settings.flashing,
+            |flashing| AccessibilitySettings {
+                flashing,
+                ..settings
+            },
+        ))
+        .add_component(toggle_row(
+            "Full Motion",
+            "Reduced Motion",
+            effects.reduced_motion,
+            |reduced_motion| EffectsSettings { reduced_motion },
+        ))
+        .add_component(toggle_row(
+            "No Audio Cues",
+            "Audio Cues",
+            audio.cues,
+            |cues| AudioSettings { cues, ..audio },
+        ))
+        .add_component(MenuButton::new(
+            "Reset",
+            ClosureMenuAction::new(move |commands: &mut Commands| {
+                commands.insert_resource(AccessibilitySettings::default());
+                commands.insert_resource(EffectsSettings::default());
+                commands.insert_resource(AudioSettings {
+                    cues: false,
+                    ..audio
+                });
+            }),
+        ))
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn theme_selection(themes: &ThemeLibrary, current: &ThemeSettings) -> MenuLayoutHorizontal {
+    themes
+        .names()
+        .fold(MenuLayoutHorizontal::new(), |row, name| {
+            let settings = ThemeSettings { name: name.into() };
+            row.add_component(MenuSelectableLabel::new(
+                theme_label(name),
+                current.name == name,
+                ClosureMenuAction::new(move |commands: &mut Commands| {
+                    commands.insert_resource(settings.clone())
+                }),
+            ))
         })
-        .unwrap_or_else(|| "[Not Set]".to_string());
+}
 
-    builder.add_component(
-        MenuLayoutHorizontal::new()
-            .add_component(MenuLabel::new(format!("{:?}", control)))
-            .add_component(MenuButton::new(
-                current_keys,
-                UpdateResourceMenuAction::new(ControlRemapping::start_remapping(control)),
-            )),
-    )
+/// "high contrast" as "High Contrast".
+fn theme_label(name: &str) -> String {
+    name.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Carries the theme's menu colors and text sizes over to egui.
+fn apply_menu_theme(mut contexts: EguiContexts, theme: Res<Theme>) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    let color = |color: Color| {
+        let [r, g, b, a] = color.to_srgba().to_u8_array();
+        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+    };
+    MenuStyle {
+        heading_size: theme.fonts.heading_size,
+        text_size: theme.fonts.text_size,
+        text: color(theme.menu.text.0),
+        background: color(theme.menu.background.0),
+        hover: color(theme.menu.hover.0),
+        active: color(theme.menu.active.0),
+    }
+    .set(contexts.ctx_mut());
 }
 
 fn init_start_game_menu(mut commands: Commands) {
     commands.init_resource::<GameSettings>();
 }
 
-fn start_game_menu(mut commands: Commands, contexts: EguiContexts, settings: ResMut<GameSettings>) {
+fn start_game_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    settings: ResMut<GameSettings>,
+    bots: Res<BotSettings>,
+    host: Option<Res<SpectatorHost>>,
+) {
     MenuBuilder::new("")
         .with_top_spacing(200.)
         .add_component(MenuLabel::new("Player 1"))
@@ -121,6 +557,16 @@ fn start_game_menu(mut commands: Commands, contexts: EguiContexts, settings: Res
                 matches!(settings.get_player1(), PlayerType::Computer(difficulty) if difficulty == &Difficulty::Impossible),
                 CommandMenuAction::new(UpdatePlayerCommand::new(1, PlayerType::Computer(Difficulty::Impossible)))
             ))
+            .add_component(MenuSelectableLabel::new(
+                "Bot",
+                matches!(settings.get_player1(), PlayerType::External),
+                CommandMenuAction::new(UpdatePlayerCommand::new(1, PlayerType::External))
+            ))
+            .add_component(MenuSelectableLabel::new(
+                "LAN Player",
+                matches!(settings.get_player1(), PlayerType::Remote),
+                CommandMenuAction::new(UpdatePlayerCommand::new(1, PlayerType::Remote))
+            ))
         )
         .add_component(MenuLabel::new("Player 2"))
         .add_component(MenuLayoutHorizontal::new()
@@ -144,43 +590,706 @@ fn start_game_menu(mut commands: Commands, contexts: EguiContexts, settings: Res
                 matches!(settings.get_player2(), PlayerType::Computer(difficulty) if difficulty == &Difficulty::Impossible),
                 CommandMenuAction::new(UpdatePlayerCommand::new(2, PlayerType::Computer(Difficulty::Impossible)))
             ))
+            .add_component(MenuSelectableLabel::new(
+                "Bot",
+                matches!(settings.get_player2(), PlayerType::External),
+                CommandMenuAction::new(UpdatePlayerCommand::new(2, PlayerType::External))
+            ))
+            .add_component(MenuSelectableLabel::new(
+                "LAN Player",
+                matches!(settings.get_player2(), PlayerType::Remote),
+                CommandMenuAction::new(UpdatePlayerCommand::new(2, PlayerType::Remote))
+            ))
         )
-        .add_component(MenuButton::new("Start Game", ChangeStateMenuAction::new(GameState::Playing)))
+        .add_component(bot_endpoints(&settings, &bots))
+        .add_component(lan_players(&settings, host.as_deref()))
+        .add_component(length_selection(settings.get_time_limit()))
+        .add_component(movement_selection(settings.get_movement()))
+        .add_component(MenuButton::new("Start Game", ClosureMenuAction::new(|commands: &mut Commands| {
+            commands.queue(UpdateModeCommand::new(GameMode::Match));
+            commands.set_state(GameState::Playing);
+        })))
         .add_component(MenuButton::new("Back", ChangeStateMenuAction::new(GameState::Main)))
         .build(contexts, &mut commands);
 }
 
+fn practice_menu(mut commands: Commands, contexts: EguiContexts, practice: Res<PracticeSettings>) {
+    let practice = *practice;
+
+    MenuBuilder::new("Practice")
+        .with_top_spacing(100.)
+        .add_component(
+            MenuLayoutHorizontal::new()
+                .add_component(MenuSelectableLabel::new(
+                    "Wall",
+                    practice.opponent == PracticeOpponent::Wall,
+                    UpdateResourceMenuAction::new(PracticeSettings {
+                        opponent: PracticeOpponent::Wall,
+                        ..practice
+                    }),
+                ))
+                .add_component(MenuSelectableLabel::new(
+                    "Launcher",
+                    practice.opponent == PracticeOpponent::Launcher,
+                    UpdateResourceMenuAction::new(PracticeSettings {
+                        opponent: PracticeOpponent::Launcher,
+                        ..practice
+                    }),
+                )),
+        )
+        .add_component(MenuSlider::new(
+            "Serve Speed",
+            practice.serve_speed,
+            100.0..=900.0,
+            move |commands, serve_speed| {
+                commands.insert_resource(PracticeSettings {
+                    serve_speed,
+                    ..practice
+                })
+            },
+        ))
+        .add_component(MenuSlider::new(
+            "Angle Range",
+            practice.angle_range,
+            0.0..=60.0,
+            move |commands, angle_range| {
+                commands.insert_resource(PracticeSettings {
+                    angle_range,
+                    ..practice
+                })
+            },
+        ))
+        .add_component(MenuSlider::new(
+            "Spin",
+            practice.spin,
+            -600.0..=600.0,
+            move |commands, spin| commands.insert_resource(PracticeSettings { spin, ..practice }),
+        ))
+        .add_component(MenuSlider::new(
+            "Launch Interval",
+            practice.interval,
+            0.5..=5.0,
+            move |commands, interval| {
+                commands.insert_resource(PracticeSettings {
+                    interval,
+                    ..practice
+                })
+            },
+        ))
+        .add_component(MenuButton::new(
+            "Start Practice",
+            ClosureMenuAction::new(|commands: &mut Commands| {
+                commands.queue(UpdateModeCommand::new(GameMode::Practice));
+                commands.set_state(GameState::Playing);
+            }),
+        ))
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn survival_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    survival: Res<SurvivalSettings>,
+    high_scores: Res<HighScores>,
+) {
+    let mut lives = MenuLayoutHorizontal::new();
+    for (label, count) in [("First Point Lost", 1), ("Three Lives", 3)] {
+        lives = lives.add_component(MenuSelectableLabel::new(
+            label,
+            survival.lives == count,
+            UpdateResourceMenuAction::new(SurvivalSettings { lives: count }),
+        ));
+    }
+
+    let mut table = MenuLayoutVertical::new().add_component(MenuLabel::new("High Scores"));
+    if high_scores.entries().is_empty() {
+        table = table.add_component(MenuLabel::new("No runs yet"));
+    }
+    for (rank, entry) in high_scores.entries().iter().enumerate() {
+        table = table.add_component(MenuLabel::new(format!(
+            "{}. {} points, {} returns (level {:.2})",
+            rank + 1,
+            entry.points,
+            entry.returns,
+            entry.level
+        )));
+    }
+
+    MenuBuilder::new("Survival")
+        .with_top_spacing(100.)
+        .add_component(lives)
+        .add_component(MenuButton::new(
+            "Start Survival",
+            ClosureMenuAction::new(|commands: &mut Commands| {
+                commands.queue(UpdateModeCommand::new(GameMode::Survival));
+                commands.set_state(GameState::Playing);
+            }),
+        ))
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .add_component(table)
+        .build(contexts, &mut commands);
+}
+
+/// Games announced on the network to join or watch, refreshed as hosts come and go.
+fn join_lan_menu(mut commands: Commands, contexts: EguiContexts, browser: Option<Res<LanBrowser>>) {
+    let mut sessions = MenuLayoutVertical::new();
+    match browser.as_deref().map(LanBrowser::sessions) {
+        None => sessions = sessions.add_component(MenuLabel::new("Network discovery unavailable")),
+        Some([]) => sessions = sessions.add_component(MenuLabel::new("Looking for games...")),
+        Some(found) => {
+            for known in found {
+                let session = &known.session;
+                let status = if session.in_match {
+                    "playing"
+                } else {
+                    "in menus"
+                };
+                let address = known.address.to_string();
+                let watch = address.clone();
+                let mut choices = MenuLayoutHorizontal::new().add_component(MenuButton::new(
+                    "Watch",
+                    ClosureMenuAction::new(move |commands: &mut Commands| {
+                        commands.insert_resource(SpectatorFeed::new(
+                            watch.clone(),
+                            Duration::from_millis(DEFAULT_DELAY_MS),
+                        ));
+                        commands.set_state(GameState::Spectating);
+                    }),
+                ));
+                for (label, side, open) in [
+                    ("Play Left", ScoreField::Left, session.open_slots[0]),
+                    ("Play Right", ScoreField::Right, session.open_slots[1]),
+                ] {
+                    if !open {
+                        continue;
+                    }
+                    let address = address.clone();
+                    choices = choices.add_component(MenuButton::new(
+                        label,
+                        ClosureMenuAction::new(move |commands: &mut Commands| {
+                            commands.insert_resource(
+                                SpectatorFeed::new(address.clone(), Duration::ZERO).joining(side),
+                            );
+                            commands.set_state(GameState::Spectating);
+                        }),
+                    ));
+                }
+                sessions = sessions
+                    .add_component(MenuLabel::new(format!(
+                        "{} ({}): {} vs {}, {}, {} watching",
+                        session.name,
+                        status,
+                        session.players[0],
+                        session.players[1],
+                        session.rules,
+                        session.spectators
+                    )))
+                    .add_component(choices);
+            }
+        }
+    }
+
+    MenuBuilder::new("Join LAN Game")
+        .with_top_spacing(100.)
+        .add_component(sessions)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+/// Top players of every mode, as reported by the leaderboard server.
+fn leaderboards_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    leaderboard: Res<Leaderboard>,
+    standings: Option<Res<LeaderboardStandings>>,
+) {
+    let mut tables = MenuLayoutHorizontal::new();
+    for mode in MODES {
+        let mut table = MenuLayoutVertical::new().add_component(MenuLabel::new(match mode {
+            "survival" => "Survival",
+            _ => "Match",
+        }));
+        match standings.as_ref().and_then(|standings| standings.get(mode)) {
+            None => table = table.add_component(MenuLabel::new("Loading...")),
+            Some(Err(error)) => table = table.add_component(MenuLabel::new(error.clone())),
+            Some(Ok(ranked)) if ranked.is_empty() => {
+                table = table.add_component(MenuLabel::new("No results yet"))
+            }
+            Some(Ok(ranked)) => {
+                for (rank, standing) in ranked.iter().enumerate() {
+                    let record = if mode == "survival" {
+                        format!("best {} points", standing.best_score)
+                    } else {
+                        format!("{} wins in {}", standing.wins, standing.matches)
+                    };
+                    table = table.add_component(MenuLabel::new(format!(
+                        "{}. {}: {}",
+                        rank + 1,
+                        standing.player,
+                        record
+                    )));
+                }
+            }
+        }
+        tables = tables.add_component(table);
+    }
+
+    let mut builder = MenuBuilder::new("Leaderboards")
+        .with_top_spacing(100.)
+        .add_component(tables);
+    if !leaderboard.pending().is_empty() {
+        builder = builder.add_component(MenuLabel::new(format!(
+            "{} results waiting to be submitted",
+            leaderboard.pending().len()
+        )));
+    }
+    builder
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn achievements_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    definitions: Res<AchievementDefinitions>,
+    progress: Res<AchievementProgress>,
+) {
+    let unlocked = definitions
+        .0
+        .iter()
+        .filter(|achievement| progress.is_unlocked(&achievement.id))
+        .count();
+    let mut list = MenuLayoutVertical::new();
+    for achievement in &definitions.0 {
+        let status = if progress.is_unlocked(&achievement.id) {
+            "Unlocked"
+        } else {
+            "Locked"
+        };
+        list = list
+            .add_component(MenuLabel::new(format!("{} - {}", achievement.name, status)))
+            .add_component(MenuLabel::new(achievement.description.clone()));
+    }
+
+    MenuBuilder::new("Achievements")
+        .with_top_spacing(50.)
+        .add_component(MenuLabel::new(format!(
+            "{} of {} unlocked",
+            unlocked,
+            definitions.0.len()
+        )))
+        .add_component(list)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(GameState::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+/// Achievements unlocked recently, with when each was unlocked in real seconds.
+#[derive(Resource, Default)]
+struct AchievementToasts(Vec<(String, f32)>);
+
+/// How long an unlocked achievement stays on screen.
+const TOAST_SECONDS: f32 = 4.;
+
+fn queue_achievement_toast(
+    trigger: Trigger<AchievementUnlocked>,
+    time: Res<Time<Real>>,
+    mut toasts: ResMut<AchievementToasts>,
+) {
+    toasts.0.push((trigger.0.name.clone(), time.elapsed_secs()));
+}
+
+/// Shows each newly unlocked achievement at the top of the screen for a few seconds,
+/// whatever screen is up.
+fn achievement_toasts(
+    mut contexts: EguiContexts,
+    time: Res<Time<Real>>,
+    mut toasts: ResMut<AchievementToasts>,
+) {
+    let now = time.elapsed_secs();
+    toasts.0.retain(|(_, shown)| now - shown < TOAST_SECONDS);
+    if toasts.0.is_empty() {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let style = MenuStyle::get(ctx);
+    egui::Area::new(egui::Id::new("achievement_toasts"))
+        .anchor(egui::Align2::CENTER_TOP, [0., 10.])
+        .interactable(false)
+        .show(ctx, |ui| {
+            for (name, _) in &toasts.0 {
+                egui::Frame::popup(ui.style())
+                    .fill(style.background)
+                    .show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(format!("Achievement unlocked: {}", name))
+                                .size(style.text_size)
+                                .color(style.text),
+                        );
+                    });
+            }
+        });
+}
+
+/// A small overlay over the watched or joined game for getting back to the menus.
+fn spectating_overlay(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    keys: Res<ActionState<GameAction>>,
+    feed: Option<Res<SpectatorFeed>>,
+) {
+    let status = feed
+        .as_ref()
+        .map_or(String::new(), |feed| match feed.seat() {
+            Some(ScoreField::Left) => format!("Playing left at {}", feed.address()),
+            Some(ScoreField::Right) => format!("Playing right at {}", feed.address()),
+            None => format!("Watching {}", feed.address()),
+        });
+    let mut leave = keys.just_pressed(&GameAction::Menu);
+
+    egui::Area::new(egui::Id::new("spectating"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10., -10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                leave |= ui.button("Leave").clicked();
+                ui.label(status);
+            });
+        });
+
+    if leave {
+        commands.set_state(GameState::JoinLan);
+    }
+}
+
+fn stop_spectating(mut commands: Commands) {
+    commands.remove_resource::<SpectatorFeed>();
+}
+
+/// Where the bot players will connect, as set in `bots.json` or on the command line.
+fn bot_endpoints(settings: &GameSettings, bots: &BotSettings) -> MenuLayoutVertical {
+    let mut layout = MenuLayoutVertical::new();
+    for (name, player, side) in [
+        ("Player 1", settings.get_player1(), ScoreField::Left),
+        ("Player 2", settings.get_player2(), ScoreField::Right),
+    ] {
+        if *player == PlayerType::External {
+            layout = layout.add_component(MenuLabel::new(format!(
+                "{} bot: {}",
+                name,
+                bots.endpoint(side)
+            )));
+        }
+    }
+    layout
+}
+
+/// How LAN players can join, which takes a game streamed with `--stream`.
+fn lan_players(settings: &GameSettings, host: Option<&SpectatorHost>) -> MenuLayoutVertical {
+    let mut layout = MenuLayoutVertical::new();
+    for (name, player) in [
+        ("Player 1", settings.get_player1()),
+        ("Player 2", settings.get_player2()),
+    ] {
+        if *player == PlayerType::Remote {
+            layout = layout.add_component(MenuLabel::new(match host {
+                Some(host) => format!("{} joins from Join LAN Game, port {}", name, host.port()),
+                None => format!("{} can only join a game started with --stream", name),
+            }));
+        }
+    }
+    layout
+}
+
+/// Match length choices: first to the points limit, or a timed match.
+fn length_selection(time_limit: Option<u32>) -> MenuLayoutVertical {
+    let mut lengths = MenuLayoutHorizontal::new();
+    for (label, limit) in [
+        ("First to Points", None),
+        ("2 Minutes", Some(120)),
+        ("5 Minutes", Some(300)),
+    ] {
+        lengths = lengths.add_component(MenuSelectableLabel::new(
+            label,
+            time_limit == limit,
+            CommandMenuAction::new(UpdateTimeLimitCommand::new(limit)),
+        ));
+    }
+
+    MenuLayoutVertical::new()
+        .add_component(MenuLabel::new("Match Length"))
+        .add_component(lengths)
+}
+
+fn movement_selection(movement: PaddleMovement) -> MenuLayoutVertical {
+    let mut layout = MenuLayoutVertical::new()
+        .add_component(MenuLabel::new("Paddle Momentum"))
+        .add_component(
+            MenuLayoutHorizontal::new()
+                .add_component(MenuSelectableLabel::new(
+                    "Off",
+                    !movement.momentum,
+                    CommandMenuAction::new(UpdateMovementCommand::new(PaddleMovement {
+                        momentum: false,
+                        ..movement
+                    })),
+                ))
+                .add_component(MenuSelectableLabel::new(
+                    "On",
+                    movement.momentum,
+                    CommandMenuAction::new(UpdateMovementCommand::new(PaddleMovement {
+                        momentum: true,
+                        ..movement
+                    })),
+                )),
+        );
+
+    if movement.momentum {
+        layout = layout
+            .add_component(MenuSlider::new(
+                "Acceleration",
+                movement.acceleration,
+                0.25..=5.0,
+                move |commands, acceleration| {
+                    commands.queue(UpdateMovementCommand::new(PaddleMovement {
+                        acceleration,
+                        ..movement
+                    }))
+                },
+            ))
+            .add_component(MenuSlider::new(
+                "Deceleration",
+                movement.deceleration,
+                0.25..=5.0,
+                move |commands, deceleration| {
+                    commands.queue(UpdateMovementCommand::new(PaddleMovement {
+                        deceleration,
+                        ..movement
+                    }))
+                },
+            ))
+            .add_component(MenuSlider::new(
+                "Max Speed",
+                movement.max_speed,
+                5.0..=20.0,
+                move |commands, max_speed| {
+                    commands.queue(UpdateMovementCommand::new(PaddleMovement {
+                        max_speed,
+                        ..movement
+                    }))
+                },
+            ));
+    }
+
+    layout
+}
+
 fn toggle_pause_game(
     keys: Res<ActionState<GameAction>>,
     state: Res<State<PausedState>>,
+    menu: Option<Res<State<PauseMenu>>>,
+    remapping: Option<Res<ControlRemapping>>,
     mut next_state: ResMut<NextState<PausedState>>,
+    mut next_menu: ResMut<NextState<PauseMenu>>,
 ) {
-    if keys.just_pressed(&GameAction::Menu) {
-        match state.get() {
-            PausedState::Playing => next_state.set(PausedState::Paused),
-            PausedState::Paused => next_state.set(PausedState::Playing),
-        }
+    if !keys.just_pressed(&GameAction::Menu)
+        || remapping.is_some_and(|remapping| remapping.is_listening())
+    {
+        return;
+    }
+
+    match (state.get(), menu.as_deref().map(State::get)) {
+        (PausedState::Playing, _) => next_state.set(PausedState::Paused),
+        (PausedState::Paused, Some(PauseMenu::Main) | None) => next_state.set(PausedState::Playing),
+        (PausedState::Paused, Some(_)) => next_menu.set(PauseMenu::Main),
     }
 }
 
-fn paused_menu(mut commands: Commands, contexts: EguiContexts) {
-    let builder = MenuBuilder::new("");
-    builder
+/// Pauses the match when the window loses focus or a human player's gamepad is
+/// unplugged.
+fn auto_pause(
+    mut focus_events: EventReader<WindowFocused>,
+    mut gamepad_events: EventReader<GamepadConnectionEvent>,
+    mut next_state: ResMut<NextState<PausedState>>,
+    input_map: Res<InputMap<GameAction>>,
+    settings: Res<GameSettings>,
+) {
+    let humans: Vec<GameAction> = [(settings.get_player1(), 1), (settings.get_player2(), 2)]
+        .into_iter()
+        .filter(|(player, _)| **player == PlayerType::Human)
+        .flat_map(|(_, player_num)| GameAction::player_actions(player_num))
+        .collect();
+
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    let unplugged = gamepad_events
+        .read()
+        .any(|event| event.disconnected() && uses_gamepad(&input_map, &humans, event.gamepad));
+
+    if lost_focus || unplugged {
+        next_state.set(PausedState::Paused);
+    }
+}
+
+fn paused_menu(mut commands: Commands, contexts: EguiContexts, settings: Res<GameSettings>) {
+    let humans = [
+        (settings.get_player1(), ScoreField::Left, "Forfeit Player 1"),
+        (
+            settings.get_player2(),
+            ScoreField::Right,
+            "Forfeit Player 2",
+        ),
+    ]
+    .into_iter()
+    .filter(|(player, _, _)| **player == PlayerType::Human)
+    .filter(|_| settings.get_mode() == GameMode::Match)
+    .collect::<Vec<_>>();
+
+    let mut builder = MenuBuilder::new("Paused")
         .add_component(MenuButton::new(
             "Resume",
             ChangeStateMenuAction::new(PausedState::Playing),
         ))
+        .add_component(MenuButton::new(
+            "Restart Match",
+            ChangeStateMenuAction::new(PauseMenu::Confirm(PauseChoice::Restart)),
+        ));
+
+    for (_, side, label) in &humans {
+        let label = if humans.len() == 1 { "Forfeit" } else { label };
+        builder = builder.add_component(MenuButton::new(
+            label,
+            ChangeStateMenuAction::new(PauseMenu::Confirm(PauseChoice::Forfeit(*side))),
+        ));
+    }
+
+    builder
+        .add_component(MenuButton::new(
+            "Settings",
+            ChangeStateMenuAction::new(PauseMenu::Settings),
+        ))
+        .add_component(MenuButton::new(
+            "Controls",
+            ChangeStateMenuAction::new(PauseMenu::Controls),
+        ))
         .add_component(MenuButton::new(
             "Main Menu",
-            ChangeStateMenuAction::new(GameState::Main),
+            ChangeStateMenuAction::new(PauseMenu::Confirm(PauseChoice::MainMenu)),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn paused_settings_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    audio: Res<AudioSettings>,
+    display: Res<DisplaySettings>,
+    themes: Res<ThemeLibrary>,
+    theme: Res<ThemeSettings>,
+) {
+    settings_components(*audio, *display, &themes, &theme)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(PauseMenu::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn paused_controls_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    keys: Res<InputMap<GameAction>>,
+    remapping: Res<ControlRemapping>,
+    schemes: Res<ControlSchemes>,
+) {
+    controls_components(&keys, &remapping, *schemes)
+        .add_component(MenuButton::new(
+            "Back",
+            ChangeStateMenuAction::new(PauseMenu::Main),
         ))
         .build(contexts, &mut commands);
 }
 
-fn end_game_menu(mut commands: Commands, contexts: EguiContexts, score: Res<Score>) {
-    let title = format!("{} Wins!", score.get_winner());
+fn confirm_menu(mut commands: Commands, contexts: EguiContexts, menu: Res<State<PauseMenu>>) {
+    let PauseMenu::Confirm(choice) = *menu.get() else {
+        return;
+    };
+
+    let builder = MenuBuilder::new("Are you sure?");
+    let builder = match choice {
+        PauseChoice::Restart => builder
+            .add_component(MenuLabel::new("The current score will be lost."))
+            .add_component(MenuButton::new(
+                "Restart Match",
+                ClosureMenuAction::new(|commands: &mut Commands| commands.trigger(RestartMatch)),
+            )),
+        PauseChoice::Forfeit(side) => builder
+            .add_component(MenuLabel::new("The match will count as a loss."))
+            .add_component(MenuButton::new(
+                "Forfeit",
+                CommandMenuAction::new(ForfeitCommand::new(side)),
+            )),
+        PauseChoice::MainMenu => builder
+            .add_component(MenuLabel::new("The current match will be abandoned."))
+            .add_component(MenuButton::new(
+                "Leave Match",
+                ChangeStateMenuAction::new(GameState::Main),
+            )),
+    };
+
+    builder
+        .add_component(MenuButton::new(
+            "Cancel",
+            ChangeStateMenuAction::new(PauseMenu::Main),
+        ))
+        .build(contexts, &mut commands);
+}
+
+fn end_game_menu(
+    mut commands: Commands,
+    contexts: EguiContexts,
+    score: Res<Score>,
+    settings: Res<GameSettings>,
+    run: Res<SurvivalRun>,
+) {
+    let mut builder = if settings.get_mode() == GameMode::Survival {
+        let mut builder = MenuBuilder::new("Game Over")
+            .add_component(MenuLabel::new(format!(
+                "Survived {} points with {} returns",
+                score.player1(),
+                run.returns()
+            )))
+            .add_component(MenuLabel::new(format!("Reached level {:.2}", run.level)));
+        if let Some(rank) = run.rank {
+            builder =
+                builder.add_component(MenuLabel::new(format!("New high score: #{}", rank + 1)));
+        }
+        builder
+    } else {
+        MenuBuilder::new(format!("{} Wins!", score.get_winner()))
+    };
+    if let Some(side) = score.forfeited() {
+        let loser = match side {
+            ScoreField::Left => "Player 1",
+            ScoreField::Right => "Player 2",
+        };
+        builder = builder.add_component(MenuLabel::new(format!("{} forfeited", loser)));
+    }
 
-    let builder = MenuBuilder::new(title);
     builder
         .add_component(MenuButton::new(
             "Restart",
@@ -200,15 +1309,50 @@ impl Plugin for MenuSystemsPlugin {
         app.add_plugins(EguiPlugin)
             .add_systems(OnEnter(GameState::Controls), init_controls_menu)
             .add_systems(OnExit(GameState::Controls), destroy_controls_menu)
+            .add_systems(OnEnter(PauseMenu::Controls), init_controls_menu)
+            .add_systems(OnExit(PauseMenu::Controls), destroy_controls_menu)
             .add_systems(OnEnter(GameState::Startgame), init_start_game_menu)
+            .add_systems(OnEnter(GameState::Practice), init_start_game_menu)
+            .add_systems(OnEnter(GameState::Survival), init_start_game_menu)
+            .add_systems(OnExit(GameState::Spectating), stop_spectating)
+            .init_resource::<AchievementToasts>()
+            .add_observer(queue_achievement_toast)
             .add_systems(
                 Update,
                 (
                     start_game_menu.in_set(StartGameSet),
                     main_menu.in_set(MainSet),
-                    (controls_menu, listen_for_keys).in_set(ControlsSet),
-                    toggle_pause_game.in_set(PlayingSet),
-                    (toggle_pause_game, paused_menu).in_set(PausedSet),
+                    (controls_menu, (ignore_menu_clicks, listen_for_keys).chain())
+                        .in_set(ControlsSet),
+                    settings_menu.in_set(SettingsSet),
+                    accessibility_menu.in_set(AccessibilitySet),
+                    practice_menu.in_set(PracticeSet),
+                    survival_menu.in_set(SurvivalSet),
+                    join_lan_menu.in_set(JoinLanSet),
+                    leaderboards_menu.in_set(LeaderboardsSet),
+                    achievements_menu.in_set(AchievementsSet),
+                    spectating_overlay.in_set(SpectatingSet),
+                    apply_menu_theme,
+                    achievement_toasts,
+                    (
+                        storage::save_on_change::<PracticeSettings>,
+                        storage::save_on_change::<SurvivalSettings>,
+                        storage::save_on_change::<HighScores>,
+                    ),
+                    (toggle_pause_game, auto_pause).in_set(PlayingSet),
+                    (
+                        toggle_pause_game,
+                        paused_menu.run_if(in_state(PauseMenu::Main)),
+                        paused_settings_menu.run_if(in_state(PauseMenu::Settings)),
+                        (
+                            paused_controls_menu,
+                            (ignore_menu_clicks, listen_for_keys).chain(),
+                        )
+                            .run_if(in_state(PauseMenu::Controls)),
+                        confirm_menu,
+                    )
+                        .chain()
+                        .in_set(PausedSet),
                     end_game_menu.in_set(EndgameSet),
                 ),
             );
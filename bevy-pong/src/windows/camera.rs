@@ -1,13 +1,161 @@
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::core::settings::{GameMode, GameSettings};
+use crate::effects::EffectsSettings;
+use crate::pong::constants::ball::MAX_BALL_SPEED;
+use crate::pong::{AfterPointScored, OnPaddleHit, OnPointScored, Score};
+
+/// Returns at this fraction of the top speed and above shake the camera.
+const HARD_HIT_SPEED: f32 = 0.6 * MAX_BALL_SPEED;
+const HARD_HIT_TRAUMA: f32 = 0.35;
+const GOAL_TRAUMA: f32 = 0.5;
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+const MAX_SHAKE_OFFSET: f32 = 12.;
+const MAX_SHAKE_ANGLE: f32 = 0.02;
+const PUNCH_SECONDS: f32 = 0.5;
+/// How far the view closes in at the height of a zoom punch.
+const PUNCH_ZOOM: f32 = 0.06;
+/// How quickly the framing eases towards a new arena size, per second.
+const FRAMING_RATE: f32 = 4.;
+
+/// Shake, zoom punch and framing of the game camera.
+///
+/// Shake follows trauma: gameplay events add to it, it wears off over time, and
+/// the camera moves by its square so small knocks barely register.
+#[derive(Component, Default)]
+pub struct CameraRig {
+    trauma: f32,
+    punch: Option<Timer>,
+    /// Projection scale that fits the arena, eased towards its target.
+    framing: Option<f32>,
+    elapsed: f32,
+}
+
+impl CameraRig {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+
+    pub fn punch(&mut self) {
+        self.punch = Some(Timer::from_seconds(PUNCH_SECONDS, TimerMode::Once));
+    }
+
+    /// Offset and rotation of the shake at the current trauma. Sines at unrelated
+    /// frequencies stand in for noise so the camera wobbles rather than jitters.
+    fn shake(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        let t = self.elapsed;
+        let offset = Vec2::new(
+            (t * 47.).sin() + (t * 31.).sin(),
+            (t * 53.).sin() + (t * 29.).sin(),
+        );
+        let angle = (t * 37.).sin();
+        (
+            offset * 0.5 * MAX_SHAKE_OFFSET * shake,
+            angle * MAX_SHAKE_ANGLE * shake,
+        )
+    }
+
+    fn zoom(&self) -> f32 {
+        self.punch.as_ref().map_or(1., |timer| {
+            1. - PUNCH_ZOOM * (timer.fraction() * std::f32::consts::PI).sin()
+        })
+    }
+}
 
 pub struct GameCameraPlugin;
 
 impl Plugin for GameCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera);
+        app.add_systems(Startup, spawn_camera)
+            .add_systems(Update, update_rig)
+            .add_observer(shake_on_hard_hit)
+            .add_observer(shake_on_goal)
+            .add_observer(punch_on_match_point);
     }
 }
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn_empty().insert(Camera2d::default());
+    commands.spawn((Camera2d, CameraRig::default()));
+}
+
+fn shake_on_hard_hit(trigger: Trigger<OnPaddleHit>, mut rigs: Query<&mut CameraRig>) {
+    if trigger.speed < HARD_HIT_SPEED {
+        return;
+    }
+    let hardness = (trigger.speed - HARD_HIT_SPEED) / (MAX_BALL_SPEED - HARD_HIT_SPEED);
+    for mut rig in &mut rigs {
+        rig.add_trauma(HARD_HIT_TRAUMA * (0.5 + hardness / 2.));
+    }
+}
+
+fn shake_on_goal(_: Trigger<OnPointScored>, mut rigs: Query<&mut CameraRig>) {
+    for mut rig in &mut rigs {
+        rig.add_trauma(GOAL_TRAUMA);
+    }
+}
+
+fn punch_on_match_point(
+    _: Trigger<AfterPointScored>,
+    settings: Res<GameSettings>,
+    score: Res<Score>,
+    mut rigs: Query<&mut CameraRig>,
+) {
+    if settings.get_mode() != GameMode::Match || !score.is_match_point() {
+        return;
+    }
+    for mut rig in &mut rigs {
+        rig.punch();
+    }
+}
+
+/// Projection scale at which the arena just fills the window.
+fn framing_scale(window: &Window, arena: Vec2) -> f32 {
+    (arena.x / window.width()).max(arena.y / window.height())
+}
+
+fn update_rig(
+    time: Res<Time>,
+    effects: Option<Res<EffectsSettings>>,
+    game: Option<Res<GameSettings>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut CameraRig, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let reduced_motion = effects.is_some_and(|effects| effects.reduced_motion);
+    let arena = game.map(|game| game.get_arena()).unwrap_or_default().size();
+    let target = windows
+        .get_single()
+        .ok()
+        .filter(|window| window.width() > 0. && window.height() > 0.)
+        .map_or(1., |window| framing_scale(window, arena));
+
+    for (mut rig, mut transform, mut projection) in &mut cameras {
+        let delta = time.delta_secs();
+        rig.elapsed += delta;
+        rig.trauma = (rig.trauma - TRAUMA_DECAY * delta).max(0.);
+        if let Some(timer) = rig.punch.as_mut() {
+            if timer.tick(time.delta()).finished() {
+                rig.punch = None;
+            }
+        }
+        if reduced_motion {
+            rig.trauma = 0.;
+            rig.punch = None;
+        }
+
+        let framing = match rig.framing {
+            Some(framing) if !reduced_motion => {
+                framing + (target - framing) * (1. - (-FRAMING_RATE * delta).exp())
+            }
+            _ => target,
+        };
+        rig.framing = Some(framing);
+
+        let (offset, angle) = rig.shake();
+        transform.translation = (offset * framing).extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(angle);
+        projection.scale = framing * rig.zoom();
+    }
 }
@@ -1,22 +1,94 @@
 use bevy::prelude::*;
-use bevy::window::WindowResolution;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+
+use crate::core::storage;
 
 const SCREEN_WIDTH: f32 = 1200.0;
 const SCREEN_HEIGHT: f32 = 1000.0;
 
+const STORAGE_NAME: &str = "display";
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+}
+
+impl DisplaySettings {
+    pub fn load() -> Self {
+        storage::load(STORAGE_NAME)
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: true,
+        }
+    }
+}
+
 pub struct GameWindowPlugin;
 
 impl Plugin for GameWindowPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)))
+        let display = DisplaySettings::load();
+
+        app.insert_resource(display)
             .add_plugins(DefaultPlugins.set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "Pong".to_string(),
                     resizable: false,
                     resolution: WindowResolution::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+                    mode: display.window_mode(),
+                    present_mode: display.present_mode(),
                     ..default()
                 }),
                 ..default()
-            }));
+            }))
+            .add_systems(
+                Update,
+                (
+                    apply_display_settings,
+                    storage::save_on_change::<DisplaySettings>,
+                ),
+            );
     }
 }
+
+fn apply_display_settings(
+    settings: Res<DisplaySettings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Ok(mut window) = window.get_single_mut() {
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+    }
+}
+
+impl storage::Persistent for DisplaySettings {
+    const STORAGE_NAME: &'static str = STORAGE_NAME;
+}
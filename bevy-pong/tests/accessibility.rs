@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_pong::accessibility::{
+    AccessibilityPlugin, AccessibilitySettings, SidePalette, GAME_SPEEDS, TEXT_SCALES,
+};
+use bevy_pong::core::settings::{GameSettings, PlayerType};
+use bevy_pong::core::states::GameState;
+use bevy_pong::core::GamePlugin;
+use bevy_pong::headless::SimulationPlugin;
+use bevy_pong::pong::{Ball, PongPlugin};
+use bevy_pong::theme::Theme;
+
+#[test]
+fn palette_and_text_scale_adapt_the_theme() {
+    let settings = AccessibilitySettings {
+        palette: SidePalette::BlueOrange,
+        text_scale: 1.5,
+        ..default()
+    };
+    let original = Theme::default();
+    let mut theme = original.clone();
+    settings.adapt(&mut theme);
+
+    assert_ne!(theme.left_paddle, original.left_paddle);
+    assert_ne!(theme.left_paddle, theme.right_paddle);
+    assert_eq!(theme.fonts.text_size, original.fonts.text_size * 1.5);
+    assert_eq!(theme.ball, original.ball);
+}
+
+#[test]
+fn large_objects_grow_the_ball_and_paddles() {
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Computer(default()));
+    settings.update_players(2, PlayerType::Computer(default()));
+
+    let mut app = App::new();
+    app.add_plugins((
+        SimulationPlugin,
+        GamePlugin,
+        PongPlugin,
+        AccessibilityPlugin,
+    ))
+    .insert_resource(settings)
+    .insert_resource(AccessibilitySettings {
+        large_objects: true,
+        ..default()
+    });
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    app.update();
+
+    let world = app.world_mut();
+    let balls: Vec<Vec3> = world
+        .query_filtered::<&Transform, With<Ball>>()
+        .iter(world)
+        .map(|transform| transform.scale)
+        .collect();
+    let paddles: Vec<Vec3> = world
+        .query_filtered::<&Transform, With<PlayerType>>()
+        .iter(world)
+        .map(|transform| transform.scale)
+        .collect();
+
+    assert_eq!(balls, [Vec3::splat(1.5)]);
+    assert_eq!(paddles, [Vec3::new(1., 1.5, 1.); 2]);
+}
+
+#[test]
+fn loaded_text_scale_and_game_speed_are_clamped_into_range() {
+    let data_dir =
+        std::env::temp_dir().join(format!("bevy_pong_accessibility_{}", std::process::id()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(
+        data_dir.join("accessibility.json"),
+        r#"{"text_scale": 40, "game_speed": -3}"#,
+    )
+    .unwrap();
+    std::env::set_var("BEVY_PONG_DATA_DIR", &data_dir);
+
+    let settings = AccessibilitySettings::load();
+    assert_eq!(settings.text_scale, *TEXT_SCALES.end());
+    assert_eq!(settings.game_speed, *GAME_SPEEDS.start());
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use bevy_pong::achievements::{
+    AchievementDefinitions, AchievementProgress, AchievementUnlocked, AchievementsPlugin,
+    MatchStats, Moment,
+};
+use bevy_pong::core::settings::{Difficulty, GameSettings, PlayerType};
+use bevy_pong::core::states::GameState;
+use bevy_pong::core::GamePlugin;
+use bevy_pong::headless::SimulationPlugin;
+use bevy_pong::pong::{Score, ScoreField};
+use std::path::Path;
+
+#[derive(Resource, Default)]
+struct Unlocked(Vec<String>);
+
+/// Plays out a 3-0 win over the Impossible computer, optionally ended by the
+/// computer forfeiting, and returns the achievements it unlocked.
+fn win_match(forfeited: bool) -> Vec<String> {
+    let data_dir =
+        std::env::temp_dir().join(format!("bevy_pong_achievements_{}", std::process::id()));
+    std::env::set_var("BEVY_PONG_DATA_DIR", data_dir);
+
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Human);
+    settings.update_players(2, PlayerType::Computer(Difficulty::Impossible));
+
+    let mut app = App::new();
+    app.add_plugins((SimulationPlugin, GamePlugin, AchievementsPlugin))
+        .insert_resource(settings)
+        .insert_resource(AchievementProgress::default())
+        .init_resource::<Score>()
+        .init_resource::<Unlocked>()
+        .add_observer(
+            |trigger: Trigger<AchievementUnlocked>, mut unlocked: ResMut<Unlocked>| {
+                unlocked.0.push(trigger.0.id.clone())
+            },
+        );
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+
+    let mut score = app.world_mut().resource_mut::<Score>();
+    score.set(3, 0);
+    if forfeited {
+        score.forfeit(ScoreField::Right);
+    }
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Endgame);
+    app.update();
+
+    std::mem::take(&mut app.world_mut().resource_mut::<Unlocked>().0)
+}
+
+#[test]
+fn winning_a_match_unlocks_its_achievements() {
+    let unlocked = win_match(false);
+    assert!(unlocked.contains(&"flawless".to_string()), "{:?}", unlocked);
+    assert!(
+        unlocked.contains(&"giant_slayer".to_string()),
+        "{:?}",
+        unlocked
+    );
+}
+
+#[test]
+fn forfeits_unlock_nothing() {
+    assert_eq!(win_match(true), Vec::<String>::new());
+}
+
+#[test]
+fn conditions_check_the_stats_at_their_moment() {
+    let definitions = AchievementDefinitions::load_from(Path::new("missing.json"));
+    let find = |id: &str| {
+        definitions
+            .0
+            .iter()
+            .find(|achievement| achievement.id == id)
+            .unwrap()
+    };
+
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Human);
+    settings.update_players(2, PlayerType::Computer(Difficulty::Impossible));
+    let mut stats = MatchStats::new(&settings);
+    assert_eq!(stats.side, Some(ScoreField::Left));
+    assert_eq!(stats.opponent_level, Some(2.));
+
+    stats.rally_hits = 49;
+    assert!(!find("marathon").is_met(Moment::PaddleHit, &stats));
+    stats.rally_hits = 50;
+    assert!(find("marathon").is_met(Moment::PaddleHit, &stats));
+    assert!(!find("marathon").is_met(Moment::MatchWon, &stats));
+
+    assert!(find("giant_slayer").is_met(Moment::MatchWon, &stats));
+    stats.opponent_level = None;
+    assert!(!find("giant_slayer").is_met(Moment::MatchWon, &stats));
+    // Stats a match doesn't have never meet a condition.
+    assert!(!find("speed_demon").is_met(Moment::PaddleHit, &stats));
+}
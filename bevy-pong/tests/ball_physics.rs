@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use proptest::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::FRAC_PI_2;
+
+use bevy_pong::cli;
+use bevy_pong::core::settings::{Arena, Difficulty, GameMode, GameSettings, PlayerType};
+use bevy_pong::core::GamePlugin;
+use bevy_pong::headless::HeadlessPlugin;
+use bevy_pong::pong::constants::ball::{MAX_BALL_SPEED, MAX_BOUNCE_ANGLE, MIN_HORIZONTAL_SPEED};
+use bevy_pong::pong::constants::{ball::STALL_TICKS, TOP_BUFFER};
+use bevy_pong::pong::{
+    constrain_velocity, Ball, OnBallStalled, OnPaddleHit, OnPointScored, PongPlugin,
+};
+
+const SERVES: usize = 1_000;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5_000))]
+
+    #[test]
+    fn constrained_velocity_keeps_moving_towards_a_goal(
+        x in -2_000f32..2_000.,
+        y in -2_000f32..2_000.,
+    ) {
+        let velocity = constrain_velocity(Vec2::new(x, y));
+
+        prop_assert!(velocity.x.abs() >= MIN_HORIZONTAL_SPEED - 1e-3);
+        prop_assert!(velocity.y.abs() <= velocity.x.abs() * MAX_BOUNCE_ANGLE.tan() + 1e-2);
+        let fastest = MAX_BALL_SPEED.max(MIN_HORIZONTAL_SPEED / MAX_BOUNCE_ANGLE.cos());
+        prop_assert!(velocity.length() <= fastest + 1e-2);
+        if x != 0. {
+            prop_assert_eq!(velocity.x.signum(), x.signum());
+        }
+    }
+
+    #[test]
+    fn constrained_velocity_is_stable(x in -2_000f32..2_000., y in -2_000f32..2_000.) {
+        let once = constrain_velocity(Vec2::new(x, y));
+        let twice = constrain_velocity(once);
+
+        prop_assert!(once.distance(twice) < 1e-2);
+    }
+}
+
+#[derive(Resource, Default)]
+struct Outcomes {
+    hits: u32,
+    points: u32,
+    stalls: u32,
+}
+
+fn match_app() -> App {
+    app_for(GameMode::Match)
+}
+
+fn app_for(mode: GameMode) -> App {
+    let mut settings = GameSettings::default();
+    settings.set_mode(mode);
+    settings.update_players(1, PlayerType::Computer(Difficulty::Easy));
+    settings.update_players(2, PlayerType::Computer(Difficulty::Easy));
+    settings.set_points_to_win(u32::MAX);
+    settings.set_seed(Some(30));
+
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin::new(1, None), GamePlugin, PongPlugin))
+        .insert_resource(settings)
+        .init_resource::<Outcomes>()
+        .add_observer(|_: Trigger<OnPaddleHit>, mut outcomes: ResMut<Outcomes>| outcomes.hits += 1)
+        .add_observer(
+            |_: Trigger<OnPointScored>, mut outcomes: ResMut<Outcomes>| outcomes.points += 1,
+        )
+        .add_observer(
+            |_: Trigger<OnBallStalled>, mut outcomes: ResMut<Outcomes>| outcomes.stalls += 1,
+        )
+        .add_systems(Startup, cli::start_match);
+    app.finish();
+    app.cleanup();
+
+    // Enter the match and let the physics world pick up the arena.
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn place_ball(app: &mut App, position: Vec2, velocity: Vec2) {
+    let world = app.world_mut();
+    let ball = world.query_filtered::<Entity, With<Ball>>().single(world);
+    let mut entity = world.entity_mut(ball);
+    entity.get_mut::<Transform>().unwrap().translation = position.extend(0.);
+    entity.get_mut::<Velocity>().unwrap().linvel = velocity;
+}
+
+fn resolved(app: &App) -> bool {
+    let outcomes = app.world().resource::<Outcomes>();
+    outcomes.hits + outcomes.points + outcomes.stalls > 0
+}
+
+#[test]
+fn random_serves_reach_a_paddle_or_goal() {
+    let mut app = match_app();
+    let mut rng = ChaCha8Rng::seed_from_u64(30);
+    let half_height = Arena::default().size().y / 2.;
+
+    for serve in 0..SERVES {
+        let angle = rng.gen_range(-FRAC_PI_2..FRAC_PI_2);
+        let direction = if rng.gen() { 1. } else { -1. };
+        let speed = rng.gen_range(0. ..1_500.);
+        let velocity = Vec2::new(direction * angle.cos(), angle.sin()) * speed;
+        let position = Vec2::new(
+            rng.gen_range(-100. ..100.),
+            rng.gen_range(-half_height + 40. ..half_height - TOP_BUFFER - 30.),
+        );
+
+        *app.world_mut().resource_mut::<Outcomes>() = Outcomes::default();
+        place_ball(&mut app, position, velocity);
+
+        let mut ticks = 0;
+        while !resolved(&app) && ticks < STALL_TICKS {
+            app.update();
+            ticks += 1;
+        }
+
+        let outcomes = app.world().resource::<Outcomes>();
+        assert_eq!(
+            outcomes.stalls, 0,
+            "serve {serve} stalled: {velocity} from {position}"
+        );
+        assert!(
+            outcomes.hits + outcomes.points > 0,
+            "serve {serve} never reached a paddle or goal: {velocity} from {position}"
+        );
+    }
+}
+
+#[test]
+fn escaped_ball_is_served_again() {
+    let mut app = match_app();
+    let ball = |app: &mut App| {
+        let world = app.world_mut();
+        world.query_filtered::<Entity, With<Ball>>().single(world)
+    };
+
+    let escaped = ball(&mut app);
+    place_ball(&mut app, Vec2::new(0., 5_000.), Vec2::new(200., 0.));
+    app.update();
+    app.update();
+
+    assert_eq!(app.world().resource::<Outcomes>().stalls, 1);
+    assert_ne!(ball(&mut app), escaped);
+}
+
+#[test]
+fn ball_past_the_open_end_in_practice_is_served_again() {
+    let mut app = app_for(GameMode::Practice);
+    let half_width = Arena::default().size().x / 2.;
+
+    place_ball(
+        &mut app,
+        Vec2::new(half_width + 50., 0.),
+        Vec2::new(400., 0.),
+    );
+    app.update();
+    app.update();
+
+    let outcomes = app.world().resource::<Outcomes>();
+    assert_eq!((outcomes.points, outcomes.stalls), (0, 1));
+}
@@ -0,0 +1,101 @@
+use bevy_pong::bot::{parse_reply, BotConnection};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// Connects a bot to a listener on a free port and returns both ends.
+fn connect() -> (BotConnection, BufReader<TcpStream>, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("tcp:{}", listener.local_addr().unwrap());
+    let bot = BotConnection::connect(&endpoint).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    (bot, BufReader::new(stream.try_clone().unwrap()), stream)
+}
+
+fn receive(observations: &mut BufReader<TcpStream>) -> serde_json::Value {
+    let mut line = String::new();
+    observations.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+fn soon() -> Instant {
+    Instant::now() + Duration::from_secs(5)
+}
+
+#[test]
+fn replies_are_parsed_into_directions() {
+    assert_eq!(parse_reply("up"), Some(1.));
+    assert_eq!(parse_reply("down\r\n"), Some(-1.));
+    assert_eq!(parse_reply(" stay "), Some(0.));
+    assert_eq!(parse_reply("0.25"), Some(0.25));
+    assert_eq!(parse_reply("-3"), Some(-1.));
+    assert_eq!(parse_reply("NaN"), None);
+    assert_eq!(parse_reply("inf"), None);
+    assert_eq!(parse_reply("left"), None);
+    assert_eq!(parse_reply(""), None);
+}
+
+#[test]
+fn replies_to_older_observations_are_skipped() {
+    let (mut bot, mut observations, mut replies) = connect();
+
+    bot.send(&json!({ "tick": 1 })).unwrap();
+    assert_eq!(bot.poll(Instant::now()).unwrap(), None);
+    bot.send(&json!({ "tick": 2 })).unwrap();
+    assert_eq!(receive(&mut observations)["tick"], 1);
+    assert_eq!(receive(&mut observations)["tick"], 2);
+
+    // The first reply comes in a tick late, after the second observation went out.
+    writeln!(replies, "up").unwrap();
+    writeln!(replies, "-0.5").unwrap();
+    assert_eq!(bot.poll(soon()).unwrap(), Some(-0.5));
+}
+
+#[test]
+fn late_replies_leave_the_paddle_still() {
+    let (mut bot, mut observations, mut replies) = connect();
+
+    bot.send(&json!({ "tick": 1 })).unwrap();
+    receive(&mut observations);
+    assert_eq!(
+        bot.poll(Instant::now() + Duration::from_millis(5)).unwrap(),
+        None
+    );
+
+    writeln!(replies, "up").unwrap();
+    bot.send(&json!({ "tick": 2 })).unwrap();
+    receive(&mut observations);
+    writeln!(replies, "banana").unwrap();
+    assert_eq!(bot.poll(soon()).unwrap(), None);
+
+    bot.send(&json!({ "tick": 3 })).unwrap();
+    receive(&mut observations);
+    writeln!(replies, "stay").unwrap();
+    assert_eq!(bot.poll(soon()).unwrap(), Some(0.));
+}
+
+#[test]
+fn closed_bot_is_disconnected() {
+    let (mut bot, observations, replies) = connect();
+    drop((observations, replies));
+
+    let exchanged = bot
+        .send(&json!({ "tick": 1 }))
+        .and_then(|_| bot.poll(soon()));
+    assert!(exchanged.is_err());
+}
+
+#[test]
+fn bot_that_stops_reading_is_dropped_without_blocking() {
+    let (mut bot, _observations, _replies) = connect();
+    let padding = "x".repeat(64 * 1024);
+
+    let started = Instant::now();
+    let stalled = (0..10_000).any(|tick| {
+        bot.send(&json!({ "tick": tick, "padding": padding }))
+            .is_err()
+    });
+    assert!(stalled);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,132 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_pong::core::controls::{
+    bindings, listen_for_keys, uses_gamepad, Binding, ControlRemapping, GameAction,
+};
+use bevy_pong::ui::ignore_widget_clicks;
+use leafwing_input_manager::prelude::*;
+
+/// Listens for `Player1Up`'s second slot while `keys` and `mouse` are pressed.
+fn remap(
+    input_map: InputMap<GameAction>,
+    keys: ButtonInput<KeyCode>,
+    mouse: ButtonInput<MouseButton>,
+) -> World {
+    let mut world = World::new();
+    world.insert_resource(input_map);
+    world.insert_resource(ControlRemapping::start_remapping(GameAction::Player1Up, 1));
+    world.insert_resource(keys);
+    world.insert_resource(mouse);
+    world.insert_resource(Time::<()>::default());
+    world.run_system_once(listen_for_keys).unwrap();
+    world
+}
+
+/// Lays out a full-window menu with one button, with the pointer at `pointer`, and
+/// returns where the button is.
+fn menu_pass(ctx: &egui::Context, pointer: egui::Pos2) -> egui::Rect {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(800., 600.),
+        )),
+        events: vec![egui::Event::PointerMoved(pointer)],
+        ..default()
+    };
+    let mut button = egui::Rect::NOTHING;
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            button = ui.button("Back").rect;
+        });
+    });
+    button
+}
+
+#[test]
+fn mouse_buttons_are_bound_unless_a_menu_widget_was_clicked() {
+    let ctx = egui::Context::default();
+    let background = egui::pos2(700., 500.);
+    let button = menu_pass(&ctx, background);
+    for (pointer, on_widget) in [(background, false), (button.center(), true)] {
+        menu_pass(&ctx, pointer);
+        menu_pass(&ctx, pointer);
+
+        let mut mouse = ButtonInput::<MouseButton>::default();
+        mouse.press(MouseButton::Right);
+        ignore_widget_clicks(&ctx, &mut mouse);
+        let world = remap(GameAction::default_input_map(), default(), mouse);
+
+        let bound = bindings(world.resource(), GameAction::Player1Up);
+        let remapping = world.resource::<ControlRemapping>();
+        if on_widget {
+            assert!(remapping.is_listening());
+            assert!(!bound.contains(&Binding::Mouse(MouseButton::Right)));
+        } else {
+            assert!(!remapping.is_listening());
+            assert_eq!(bound[1], Binding::Mouse(MouseButton::Right));
+        }
+    }
+}
+
+#[test]
+fn remapping_keeps_gamepad_bindings() {
+    let mut input_map = GameAction::default_input_map();
+    input_map.insert(GameAction::Player1Up, GamepadButton::DPadUp);
+    let before = bindings(&input_map, GameAction::Player1Up);
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::KeyI);
+
+    let world = remap(input_map, keys, default());
+
+    let input_map = world.resource::<InputMap<GameAction>>();
+    let after = bindings(input_map, GameAction::Player1Up);
+    assert_eq!(after[0], before[0]);
+    assert_eq!(after.get(1), Some(&Binding::Key(KeyCode::KeyI)));
+    assert!(uses_gamepad(
+        input_map,
+        &GameAction::player_actions(1),
+        Entity::from_raw(3)
+    ));
+}
+
+#[test]
+fn binding_an_actions_own_key_again_is_not_a_conflict() {
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::ArrowUp);
+
+    let mut world = World::new();
+    world.insert_resource(GameAction::default_input_map());
+    world.insert_resource(ControlRemapping::start_remapping(GameAction::Player1Up, 1));
+    world.insert_resource(keys);
+    world.insert_resource(ButtonInput::<MouseButton>::default());
+    world.insert_resource(Time::<()>::default());
+    world.run_system_once(listen_for_keys).unwrap();
+
+    let remapping = world.resource::<ControlRemapping>();
+    assert!(remapping.conflict().is_none());
+    assert!(!remapping.is_listening());
+    assert_eq!(
+        bindings(world.resource(), GameAction::Player1Up),
+        [Binding::Key(KeyCode::ArrowUp)]
+    );
+}
+
+#[test]
+fn only_gamepads_a_player_reads_are_theirs() {
+    let pad = Entity::from_raw(10);
+    let other_pad = Entity::from_raw(11);
+    let player1 = GameAction::player_actions(1);
+    let player2 = GameAction::player_actions(2);
+
+    let mut input_map = GameAction::default_input_map();
+    assert!(!uses_gamepad(&input_map, &player1, pad));
+
+    input_map.insert(GameAction::Player1Up, GamepadButton::DPadUp);
+    assert!(uses_gamepad(&input_map, &player1, pad));
+    assert!(!uses_gamepad(&input_map, &player2, pad));
+
+    input_map.set_gamepad(pad);
+    assert!(uses_gamepad(&input_map, &player1, pad));
+    assert!(!uses_gamepad(&input_map, &player1, other_pad));
+}
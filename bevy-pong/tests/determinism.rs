@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use bevy_pong::cli;
+use bevy_pong::core::rng::{GameRng, RngStream};
+use bevy_pong::core::settings::{Difficulty, GameMode, GameSettings, PlayerType};
+use bevy_pong::core::states::GameState;
+use bevy_pong::core::GamePlugin;
+use bevy_pong::headless::HeadlessPlugin;
+use bevy_pong::pong::constants::TICK_RATE;
+use bevy_pong::pong::{MatchClock, PongPlugin, Score};
+
+/// Rallies between computer players run long, so this leaves room for several.
+const MAX_UPDATES: u32 = 40_000;
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    player1: u32,
+    player2: u32,
+    ticks: u64,
+    state: GameState,
+}
+
+fn run_headless_match(seed: u64) -> Outcome {
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Computer(Difficulty::Easy));
+    settings.update_players(2, PlayerType::Computer(Difficulty::Difficult));
+    settings.set_points_to_win(1);
+    settings.set_seed(Some(seed));
+
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin::new(1, None), GamePlugin, PongPlugin))
+        .insert_resource(settings)
+        .add_systems(Startup, cli::start_match);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..MAX_UPDATES {
+        app.update();
+        if app.should_exit().is_some() {
+            break;
+        }
+    }
+
+    let score = app.world().resource::<Score>();
+    Outcome {
+        player1: score.player1(),
+        player2: score.player2(),
+        ticks: app.world().resource::<MatchClock>().ticks(),
+        state: app.world().resource::<State<GameState>>().get().clone(),
+    }
+}
+
+#[test]
+fn same_seed_replays_same_match() {
+    let first = run_headless_match(42);
+    let second = run_headless_match(42);
+
+    assert_eq!(first, second);
+    assert_eq!(first.state, GameState::Endgame);
+    assert!(first.ticks > 0);
+}
+
+#[test]
+fn different_seeds_play_different_matches() {
+    let first = run_headless_match(42);
+    let second = run_headless_match(43);
+
+    assert_eq!(first.state, GameState::Endgame);
+    assert_eq!(second.state, GameState::Endgame);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn same_seed_yields_same_sequence() {
+    let mut a = GameRng::new(7);
+    let mut b = GameRng::new(7);
+
+    for _ in 0..100 {
+        assert_eq!(
+            a.stream(RngStream::Serve).gen::<u64>(),
+            b.stream(RngStream::Serve).gen::<u64>()
+        );
+    }
+}
+
+#[test]
+fn different_seeds_yield_different_sequences() {
+    let mut a = GameRng::new(7);
+    let mut b = GameRng::new(8);
+
+    let first: Vec<u64> = (0..10).map(|_| a.stream(RngStream::Serve).gen()).collect();
+    let second: Vec<u64> = (0..10).map(|_| b.stream(RngStream::Serve).gen()).collect();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn streams_are_independent() {
+    let mut quiet = GameRng::new(7);
+    let mut busy = GameRng::new(7);
+
+    for _ in 0..50 {
+        busy.stream(RngStream::Ai).gen::<u64>();
+    }
+
+    assert_eq!(
+        quiet.stream(RngStream::Serve).gen::<u64>(),
+        busy.stream(RngStream::Serve).gen::<u64>()
+    );
+}
+
+#[test]
+fn survival_ignores_a_leftover_time_limit() {
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Computer(Difficulty::Difficult));
+    settings.set_mode(GameMode::Survival);
+    settings.set_time_limit(Some(1));
+    settings.set_seed(Some(7));
+
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin::new(1, None), GamePlugin, PongPlugin))
+        .insert_resource(settings)
+        .add_systems(Startup, cli::start_match);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..(3. * TICK_RATE) as u32 {
+        app.update();
+    }
+
+    let clock = app.world().resource::<MatchClock>();
+    assert!(clock.seconds() > 1.);
+    assert!(!clock.is_timed());
+    assert!(!clock.is_time_up());
+}
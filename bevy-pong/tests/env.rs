@@ -0,0 +1,76 @@
+use bevy_pong::core::settings::{Difficulty, GameSettings, PlayerType};
+use bevy_pong::env::{Action, Observation, PongEnv, VecPongEnv};
+
+const MAX_STEPS: usize = 20_000;
+
+fn short_match() -> GameSettings {
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::External);
+    settings.update_players(2, PlayerType::Computer(Difficulty::Easy));
+    settings.set_points_to_win(1);
+    settings
+}
+
+fn rollout(env: &mut PongEnv, seed: u64, steps: usize) -> Vec<Observation> {
+    let mut observations = vec![env.reset(seed)];
+    for _ in 0..steps {
+        observations.push(env.step(Action::Up, Action::Stay).0);
+    }
+    observations
+}
+
+#[test]
+fn same_seed_replays_same_observations() {
+    let mut env = PongEnv::new(short_match());
+    let first = rollout(&mut env, 11, 200);
+    let second = rollout(&mut env, 11, 200);
+
+    assert_eq!(first, second);
+    assert_ne!(first[0].ball_vx, 0.);
+}
+
+#[test]
+fn idle_agent_loses_the_point_and_the_match() {
+    let mut env = PongEnv::new(short_match());
+    env.reset(3);
+
+    let mut rewards = Vec::new();
+    for _ in 0..MAX_STEPS {
+        let (_, reward, done) = env.step(Action::Stay, Action::Stay);
+        rewards.push(reward);
+        if done {
+            break;
+        }
+    }
+
+    assert!(env.is_done(), "match never finished");
+    assert_eq!(rewards.iter().sum::<f32>(), -1.);
+    assert_eq!(env.step(Action::Stay, Action::Stay).1, 0.);
+    assert_eq!(env.reset(3).score2, 0);
+}
+
+#[test]
+fn batch_resets_finished_environments() {
+    let mut envs = VecPongEnv::new(2, short_match);
+    let mut observations = envs.reset(0);
+    assert_eq!(observations.len(), 2);
+
+    let mut finished = 0;
+    for _ in 0..MAX_STEPS {
+        let actions = vec![(Action::Stay, Action::Stay); envs.len()];
+        let results = envs.step(&actions);
+        finished += results.iter().filter(|(_, _, done)| *done).count();
+        observations = results
+            .into_iter()
+            .map(|(observation, _, _)| observation)
+            .collect();
+        if finished >= 2 {
+            break;
+        }
+    }
+
+    assert!(finished >= 2);
+    assert!(observations
+        .iter()
+        .all(|observation| observation.score2 <= 1));
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use std::fs;
+
+use bevy_pong::cli;
+use bevy_pong::core::settings::{Difficulty, GameSettings, PlayerType};
+use bevy_pong::core::GamePlugin;
+use bevy_pong::events::{EventLog, EventRecord, GameplayEvent, GameplayEventsPlugin};
+use bevy_pong::headless::HeadlessPlugin;
+use bevy_pong::pong::PongPlugin;
+
+const MAX_UPDATES: u32 = 20_000;
+
+#[test]
+fn a_match_is_logged_as_json_lines() {
+    let path = std::env::temp_dir().join(format!("bevy_pong_events_{}.jsonl", std::process::id()));
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Computer(Difficulty::Easy));
+    settings.update_players(2, PlayerType::Computer(Difficulty::Difficult));
+    settings.set_points_to_win(2);
+    settings.set_seed(Some(7));
+
+    let mut app = App::new();
+    app.add_plugins((
+        HeadlessPlugin::new(1, None),
+        GamePlugin,
+        PongPlugin,
+        GameplayEventsPlugin,
+    ))
+    .insert_resource(settings)
+    .insert_resource(EventLog::create(&path).unwrap())
+    .add_systems(Startup, cli::start_match);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..MAX_UPDATES {
+        app.update();
+        if app.should_exit().is_some() {
+            break;
+        }
+    }
+    drop(app);
+
+    let records: Vec<EventRecord> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        records.first().map(|record| &record.event),
+        Some(GameplayEvent::ServeStarted { .. })
+    ));
+    let Some(GameplayEvent::MatchEnded { result }) = records.last().map(|record| &record.event)
+    else {
+        panic!("the match didn't end: {:?}", records.last());
+    };
+    let points = records
+        .iter()
+        .filter(|record| matches!(record.event, GameplayEvent::PointScored { .. }))
+        .count();
+    assert_eq!(points as u32, result.score[0] + result.score[1]);
+    assert!(records
+        .iter()
+        .any(|record| matches!(record.event, GameplayEvent::PaddleHit { .. })));
+    assert!(records.windows(2).all(|pair| pair[0].tick <= pair[1].tick));
+}
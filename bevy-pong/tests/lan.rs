@@ -0,0 +1,60 @@
+use bevy_pong::core::settings::{GameSettings, PlayerType};
+use bevy_pong::lan::{LanAdvertiser, LanBrowser, LanSession};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Refreshes `browser` until `done` holds, or gives up after a second.
+fn wait_for(browser: &mut LanBrowser, done: impl Fn(&LanBrowser) -> bool) -> bool {
+    for _ in 0..100 {
+        browser.update(Instant::now());
+        if done(browser) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn browsers_on_one_machine_see_hosts_come_and_go() {
+    let mut first = LanBrowser::open(0).unwrap();
+    let discovery_port = first.port();
+    let mut second = LanBrowser::open(discovery_port).unwrap();
+
+    let session = LanSession::new("left", 7000, &GameSettings::default());
+    let left = LanAdvertiser::new(session.clone(), discovery_port).unwrap();
+    let right = LanAdvertiser::new(
+        LanSession::new("right", 7001, &GameSettings::default()),
+        discovery_port,
+    )
+    .unwrap();
+    left.announce().unwrap();
+    right.announce().unwrap();
+
+    for browser in [&mut first, &mut second] {
+        assert!(wait_for(browser, |browser| browser.sessions().len() == 2));
+        let found = &browser.sessions()[0];
+        assert_eq!(found.session, session);
+        assert_eq!(found.address.port(), 7000);
+    }
+
+    drop(left);
+    for browser in [&mut first, &mut second] {
+        assert!(wait_for(browser, |browser| browser.sessions().len() == 1));
+        assert_eq!(browser.sessions()[0].session.name, "right");
+    }
+}
+
+#[test]
+fn sides_left_to_lan_players_are_open_slots() {
+    let mut settings = GameSettings::default();
+    assert_eq!(
+        LanSession::new("host", 7000, &settings).open_slots,
+        [false, false]
+    );
+
+    settings.update_players(2, PlayerType::Remote);
+    let session = LanSession::new("host", 7000, &settings);
+    assert_eq!(session.open_slots, [false, true]);
+    assert_eq!(session.players[1], "lan");
+}
@@ -0,0 +1,136 @@
+use bevy_pong::leaderboard::{
+    serve, FileBackend, HttpBackend, Leaderboard, LeaderboardBackend, LeaderboardError,
+    MatchResult, Standing, RETRY_INTERVAL,
+};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn result(players: [&str; 2], score: [u32; 2]) -> MatchResult {
+    MatchResult {
+        mode: "match".into(),
+        players: players.map(String::from),
+        score,
+        winner: Some(if score[0] > score[1] { 0 } else { 1 }),
+        rules: "first to 5, classic arena".into(),
+        seconds: 60.,
+        finished_at: 0,
+    }
+}
+
+fn results_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bevy_pong_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Starts a file-backed server on `listener` and returns its URL.
+fn start_server_on(listener: TcpListener, name: &str) -> String {
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let backend = FileBackend::new(results_file(name));
+    thread::spawn(move || serve(listener, &backend));
+    url
+}
+
+/// Starts a file-backed server on a free port and returns its URL.
+fn start_server(name: &str) -> String {
+    start_server_on(TcpListener::bind("127.0.0.1:0").unwrap(), name)
+}
+
+#[test]
+fn server_ranks_submitted_results() {
+    let client = HttpBackend::new(start_server("ranks"));
+    client.submit(&result(["ana", "ai:easy"], [5, 2])).unwrap();
+    client.submit(&result(["ana", "bo"], [5, 4])).unwrap();
+    client.submit(&result(["bo", "ai:easy"], [1, 5])).unwrap();
+
+    let standings = client.standings("match", 10).unwrap();
+    let ranked: Vec<(&str, u32, u32)> = standings
+        .iter()
+        .map(|standing| (standing.player.as_str(), standing.wins, standing.matches))
+        .collect();
+    assert_eq!(ranked, [("ana", 2, 2), ("ai:easy", 1, 2), ("bo", 0, 2)]);
+    assert!(client.standings("survival", 10).unwrap().is_empty());
+}
+
+/// Polls until the queue has `len` results left or the submission is given up on.
+fn poll_until(leaderboard: &mut Leaderboard, now: Instant, len: usize) -> bool {
+    for _ in 0..200 {
+        leaderboard.poll(now);
+        if leaderboard.pending().len() == len {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn queued_results_are_retried_until_the_server_answers() {
+    let reserved = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = reserved.local_addr().unwrap();
+    drop(reserved);
+
+    let mut leaderboard =
+        Leaderboard::new(HttpBackend::new(format!("http://{}", address)), Vec::new());
+    leaderboard.enqueue(result(["ana", "bo"], [5, 1]));
+    leaderboard.enqueue(result(["bo", "ana"], [5, 3]));
+    let now = Instant::now();
+    assert!(!poll_until(&mut leaderboard, now, 0));
+    assert_eq!(leaderboard.pending().len(), 2);
+
+    // The server comes up at the address, but nothing is sent before the retry is due.
+    start_server_on(TcpListener::bind(address).unwrap(), "retry");
+    assert!(!poll_until(&mut leaderboard, now, 0));
+    assert!(poll_until(&mut leaderboard, now + RETRY_INTERVAL, 0));
+}
+
+/// Refuses results from players named "cheat" and keeps the rest.
+#[derive(Default, Clone)]
+struct PickyBackend {
+    accepted: Arc<Mutex<Vec<MatchResult>>>,
+}
+
+impl LeaderboardBackend for PickyBackend {
+    fn submit(&self, result: &MatchResult) -> Result<(), LeaderboardError> {
+        if result.players.iter().any(|player| player == "cheat") {
+            return Err(LeaderboardError::Rejected("400 Bad Request".into()));
+        }
+        self.accepted.lock().unwrap().push(result.clone());
+        Ok(())
+    }
+
+    fn standings(&self, _: &str, _: usize) -> Result<Vec<Standing>, LeaderboardError> {
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn refused_results_are_dropped_without_holding_up_the_queue() {
+    let backend = PickyBackend::default();
+    let mut leaderboard = Leaderboard::new(backend.clone(), Vec::new());
+    leaderboard.enqueue(result(["cheat", "bo"], [5, 0]));
+    leaderboard.enqueue(result(["ana", "bo"], [5, 1]));
+
+    assert!(poll_until(&mut leaderboard, Instant::now(), 0));
+    let accepted = backend.accepted.lock().unwrap();
+    assert_eq!(*accepted, [result(["ana", "bo"], [5, 1])]);
+}
+
+#[test]
+fn server_refuses_oversized_bodies() {
+    let url = start_server("oversized");
+    let mut stream = TcpStream::connect(url.trim_start_matches("http://")).unwrap();
+    write!(
+        stream,
+        "POST /results HTTP/1.1\r\nContent-Length: 4000000000\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy_pong::core::settings::{GameSettings, PlayerType};
+use bevy_pong::core::states::GameState;
+use bevy_pong::core::GamePlugin;
+use bevy_pong::headless::SimulationPlugin;
+use bevy_pong::pong::{ExternalInput, PongPlugin, ScoreField};
+use bevy_pong::spectator::{MatchFrame, SpectatorFeed, SpectatorHost, SpectatorHostPlugin};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn host_app(player2: PlayerType) -> App {
+    let mut settings = GameSettings::default();
+    settings.update_players(1, PlayerType::Computer(default()));
+    settings.update_players(2, player2);
+
+    let mut app = App::new();
+    app.add_plugins((
+        SimulationPlugin,
+        GamePlugin,
+        PongPlugin,
+        SpectatorHostPlugin { port: 0 },
+    ))
+    .insert_resource(settings);
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app
+}
+
+/// Updates the host, and `feed` if given, until `done` holds or a second has passed.
+fn update_until(
+    app: &mut App,
+    mut feed: Option<&mut SpectatorFeed>,
+    done: impl Fn(&App) -> bool,
+) -> bool {
+    for _ in 0..100 {
+        if let Some(feed) = feed.as_deref_mut() {
+            feed.update(Instant::now());
+        }
+        app.update();
+        if done(app) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn right_direction(app: &App) -> f32 {
+    app.world()
+        .resource::<ExternalInput>()
+        .direction(ScoreField::Right)
+}
+
+#[test]
+fn late_viewer_starts_from_the_latest_frame() {
+    let mut app = host_app(PlayerType::Computer(default()));
+    for _ in 0..100 {
+        app.update();
+    }
+
+    let port = app.world().resource::<SpectatorHost>().port();
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    for _ in 0..20 {
+        if app.world().resource::<SpectatorHost>().viewer_count() > 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+    assert_eq!(app.world().resource::<SpectatorHost>().viewer_count(), 1);
+
+    let mut lines = BufReader::new(stream).lines();
+    let first: MatchFrame = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert!(first.tick >= 90, "joined at tick {}", first.tick);
+    assert_eq!(first.score, "0 - 0");
+    assert_eq!(first.paddles.len(), 2);
+    assert_eq!(first.balls.len(), 1);
+
+    app.update();
+    let second: MatchFrame = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert!(second.tick > first.tick);
+}
+
+#[test]
+fn viewer_joins_the_open_side_until_it_leaves() {
+    let mut app = host_app(PlayerType::Remote);
+    app.update();
+    let port = app.world().resource::<SpectatorHost>().port();
+
+    let mut player = SpectatorFeed::new(format!("127.0.0.1:{}", port), Duration::ZERO)
+        .joining(ScoreField::Right);
+    player.update(Instant::now());
+    player.steer(1.);
+    assert!(update_until(&mut app, Some(&mut player), |app| {
+        right_direction(app) == 1.
+    }));
+    let host = app.world().resource::<SpectatorHost>();
+    assert!(host.seat_taken(ScoreField::Right));
+    assert!(!host.seat_taken(ScoreField::Left));
+
+    // The side is taken, and the left side isn't open at all.
+    let mut latecomer = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    writeln!(latecomer, "join right\ndown\njoin left\ndown").unwrap();
+    assert!(!update_until(&mut app, None, |app| right_direction(app) != 1.));
+    assert!(!app
+        .world()
+        .resource::<SpectatorHost>()
+        .seat_taken(ScoreField::Left));
+
+    drop(player);
+    assert!(update_until(&mut app, None, |app| {
+        !app.world()
+            .resource::<SpectatorHost>()
+            .seat_taken(ScoreField::Right)
+    }));
+    assert_eq!(right_direction(&app), 0.);
+
+    writeln!(latecomer, "join right\ndown").unwrap();
+    assert!(update_until(&mut app, None, |app| right_direction(app) == -1.));
+}
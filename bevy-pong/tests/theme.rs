@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use bevy_pong::theme::{Theme, ThemeLibrary};
+use std::fs;
+
+#[test]
+fn theme_files_replace_and_add_to_the_built_in_themes() {
+    let dir = std::env::temp_dir().join(format!("bevy_pong_themes_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut classic = Theme::default();
+    classic.ball.0 = Color::srgb(1., 0., 0.);
+    let mut custom = classic.clone();
+    custom.name = "custom".into();
+    fs::write(
+        dir.join("classic.json"),
+        serde_json::to_string(&classic).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("custom.json"),
+        serde_json::to_string(&custom).unwrap(),
+    )
+    .unwrap();
+    fs::write(dir.join("broken.json"), "{").unwrap();
+
+    let library = ThemeLibrary::load_from(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<&str> = library.names().collect();
+    assert_eq!(names, ["classic", "neon", "high contrast", "custom"]);
+    assert_eq!(library.get("classic"), Some(&classic));
+    assert_eq!(library.get("custom"), Some(&custom));
+}
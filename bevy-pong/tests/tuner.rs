@@ -0,0 +1,150 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use bevy_pong::cli;
+use bevy_pong::core::settings::{AiPresets, AiProfile, Difficulty, GameSettings, PlayerType};
+use bevy_pong::env::PongEnv;
+use bevy_pong::tuner::{fit, tune, win_rate, TunerConfig, TuningProgress, REFERENCE};
+
+/// Barely moves and aims anywhere, so it loses its matches quickly.
+const HOPELESS: AiProfile = AiProfile {
+    speed: 2.,
+    reaction_ticks: 30,
+    prediction_noise: 120.,
+    aggression: 0.,
+};
+
+fn one_point_matches() -> GameSettings {
+    let mut settings = GameSettings::default();
+    settings.set_points_to_win(1);
+    settings
+}
+
+fn quick_config() -> TunerConfig {
+    TunerConfig {
+        generations: 2,
+        population: 2,
+        matches: 2,
+        seed: 5,
+    }
+}
+
+#[test]
+fn custom_players_round_trip_through_text() {
+    let player = PlayerType::Computer(Difficulty::Custom(AiProfile {
+        speed: 9.5,
+        reaction_ticks: 4,
+        prediction_noise: 22.25,
+        aggression: 0.5,
+    }));
+
+    let text = player.to_string();
+    assert_eq!(text, "ai:custom:9.5,4,22.25,0.5");
+    assert!(text.parse::<PlayerType>().unwrap() == player);
+    assert!("ai:custom:9.5,4".parse::<PlayerType>().is_err());
+    assert!("ai:custom:9.5,4,1,0,7".parse::<PlayerType>().is_err());
+}
+
+#[test]
+fn out_of_range_difficulties_are_rejected() {
+    for text in [
+        "ai:custom:10,0,-5,0",
+        "ai:custom:NaN,0,5,0",
+        "ai:custom:inf,0,5,0",
+        "ai:custom:-1,0,5,0",
+        "ai:custom:10,0,5,1.5",
+        "ai:custom:10,-2,5,0",
+        "ai:inf",
+        "ai:NaN",
+    ] {
+        assert!(text.parse::<PlayerType>().is_err(), "{} was accepted", text);
+    }
+
+    let args = ["--p2", "ai:custom:10,0,-5,0"].map(String::from);
+    assert!(cli::parse(args.into_iter()).is_err());
+}
+
+#[test]
+fn loaded_presets_are_clamped_into_range() {
+    let data_dir = std::env::temp_dir().join(format!("bevy_pong_tuner_{}", std::process::id()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let profile = r#"{"speed": -4, "reaction_ticks": 3, "prediction_noise": -20, "aggression": 3}"#;
+    std::fs::write(
+        data_dir.join("ai_presets.json"),
+        format!(r#"{{"impossible": {}}}"#, profile),
+    )
+    .unwrap();
+    std::env::set_var("BEVY_PONG_DATA_DIR", &data_dir);
+
+    let presets = AiPresets::load();
+    assert_eq!(presets.easy, AiPresets::default().easy);
+    let beyond = presets.profile(&Difficulty::Level(3.));
+    assert!(beyond.is_valid(), "{:?}", beyond);
+    assert_eq!(beyond.aggression, 1.);
+}
+
+#[test]
+fn levels_past_impossible_never_slow_down() {
+    let mut presets = AiPresets::default();
+    presets.impossible.speed = presets.difficult.speed - 2.;
+
+    let impossible = presets.profile(&Difficulty::Impossible);
+    let beyond = presets.profile(&Difficulty::Level(4.));
+    assert!(beyond.speed >= impossible.speed);
+}
+
+#[test]
+fn hopeless_candidate_loses_every_match() {
+    let mut env = PongEnv::new(one_point_matches());
+
+    assert_eq!(win_rate(&mut env, HOPELESS, REFERENCE, &quick_config()), 0.);
+}
+
+#[test]
+fn fit_stops_once_the_target_is_met() {
+    let mut env = PongEnv::new(one_point_matches());
+    let mut generations = 0;
+
+    let (profile, rate) = fit(
+        &mut env,
+        HOPELESS,
+        0.,
+        &quick_config(),
+        &mut ChaCha8Rng::seed_from_u64(5),
+        &mut |progress| {
+            if let TuningProgress::Generation { .. } = progress {
+                generations += 1;
+            }
+        },
+    );
+
+    assert_eq!(rate, 0.);
+    assert_eq!(profile, HOPELESS);
+    assert_eq!(generations, 1);
+}
+
+#[test]
+fn tuning_is_reproducible() {
+    let config = TunerConfig {
+        generations: 0,
+        ..quick_config()
+    };
+    let presets = AiPresets {
+        easy: HOPELESS,
+        difficult: HOPELESS,
+        impossible: HOPELESS,
+    };
+    let run = || {
+        let mut fitted = Vec::new();
+        let tuned = tune(one_point_matches(), presets, &config, |progress| {
+            if let TuningProgress::Fitted { name, .. } = progress {
+                fitted.push(name);
+            }
+        });
+        (tuned, fitted)
+    };
+
+    let (first, fitted) = run();
+    assert_eq!(fitted, ["easy", "difficult", "impossible"]);
+    assert_eq!(first, run().0);
+}